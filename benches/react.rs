@@ -0,0 +1,107 @@
+extern crate advent2018_rs;
+
+use std::time::{Duration, Instant};
+
+use advent2018_rs::day05::react;
+use advent2018_rs::input::EmbeddedInput;
+
+// The original quadratic reaction, kept here as the baseline
+fn react_naive(polymer: &[u8]) -> Vec<u8> {
+    let mut polymer = polymer.to_vec();
+    let mut idx = 0;
+
+    loop {
+        let copy = polymer.clone();
+        if idx + 1 >= copy.len() {
+            return polymer;
+        }
+
+        let a = copy[idx];
+        let b = copy[idx+1];
+        if a != b && a.eq_ignore_ascii_case(&b) {
+            let mut lhs = copy[.. idx].to_vec();
+            let mut rhs = copy[idx+2 ..].to_vec();
+            lhs.append(&mut rhs);
+            polymer = lhs;
+            idx = idx.saturating_sub(1);
+            continue;
+        }
+
+        idx += 1;
+    }
+}
+
+// Deterministic pseudo-random polymer over a few unit types, so plenty of it reacts
+fn synthetic(len: usize) -> Vec<u8> {
+    let mut state: u64 = 0x2018_0005;
+    (0 .. len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let letter = b'a' + (state % 4) as u8;
+            if (state >> 32) & 1 == 0 { letter } else { letter.to_ascii_uppercase() }
+        })
+        .collect()
+}
+
+fn time<F>(f: F) -> (usize, Duration)
+where
+    F: Fn() -> Vec<u8>,
+{
+    let start = Instant::now();
+    let len = f().len();
+    (len, start.elapsed())
+}
+
+// How the naive baseline is timed for an input
+#[derive(Copy, Clone)]
+enum Naive {
+    Measure,
+    // Scaled quadratically from the largest measured input; too slow to run
+    Extrapolate,
+}
+
+// Prints a row, returning the largest naive timing measured so far as (units, time)
+fn report(name: &str, polymer: &[u8], naive: Naive, measured: Option<(usize, Duration)>) -> Option<(usize, Duration)> {
+    let (len, stack) = time(|| react(polymer));
+    let (measured, naive) = match (naive, measured) {
+        (Naive::Measure, _) => {
+            let (naive_len, naive) = time(|| react_naive(polymer));
+            assert_eq!(len, naive_len);
+            (Some((polymer.len(), naive)), Some((format!("{:>12.3?}", naive), naive)))
+        },
+        (Naive::Extrapolate, Some((units, naive))) => {
+            let scale = polymer.len() as f64 / units as f64;
+            let estimate = naive.mul_f64(scale * scale);
+            (measured, Some((format!("{:>12}", format!("~{:.1?}", estimate)), estimate)))
+        },
+        (Naive::Extrapolate, None) => (None, None),
+    };
+    let (naive, speedup) = match naive {
+        Some((naive, time)) => (naive, format!("{:>8.0}x", time.as_secs_f64() / stack.as_secs_f64())),
+        None => (format!("{:>12}", "-"), format!("{:>9}", "-")),
+    };
+    println!("{:<16}  {:>10}  {:>10}  {:>12.3?}  {}  {}", name, polymer.len(), len, stack, naive, speedup);
+    measured
+}
+
+fn main() {
+    println!("{:<16}  {:>10}  {:>10}  {:>12}  {:>12}  {:>9}", "polymer", "units", "reacted", "stack", "naive", "speedup");
+
+    let puzzle = EmbeddedInput::get(5).unwrap().trim().as_bytes();
+    report("05a.txt", puzzle, Naive::Measure, None);
+
+    // The naive version is quadratic: it's run up to 1M units (tens of seconds), and the
+    // larger inputs show its time scaled up from there, marked with `~`
+    let mut measured = None;
+    for &(name, len, naive) in &[
+        ("synthetic 64K", 1 << 16, Naive::Measure),
+        ("synthetic 256K", 1 << 18, Naive::Measure),
+        ("synthetic 1M", 1 << 20, Naive::Measure),
+        ("synthetic 4M", 1 << 22, Naive::Extrapolate),
+        ("synthetic 16M", 1 << 24, Naive::Extrapolate),
+    ] {
+        measured = report(name, &synthetic(len), naive, measured);
+    }
+}
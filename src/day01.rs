@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+
+use crate::parse;
+use crate::error::{Error, ParseError};
+use crate::solver::{Answer, Part, Solver};
+
+// Frequency changes separated by newlines, commas or whitespace, as in
+// `+1, -2, +3, +1`; each is paired with the 1-based line it is on
+pub fn frequency_changes(input: &str) -> Result<Vec<(usize, isize)>, ParseError> {
+    input.lines()
+        .enumerate()
+        .flat_map(|(idx, line)| {
+            line.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|token| !token.is_empty())
+                .map(move |token| (idx + 1, token))
+        })
+        .map(|(line, token)| {
+            parse::checked_signed_integer(token)
+                .map(|delta| (line, delta))
+                .map_err(|e| e.at_line(line))
+        })
+        .collect()
+}
+
+// Where the running frequency first reaches a value it has had before
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FirstRepeat {
+    pub frequency: isize,
+    // Complete passes over the list before the change that repeats
+    pub cycle: usize,
+    // 0-based index of that change within the list, and its line
+    pub position: usize,
+    pub line: usize,
+    // Changes applied in the first pass when the frequency was first reached; 0 is the start
+    pub first_seen: usize,
+}
+
+// After `steps` changes the frequency is `cycle * drift + sums[index]`, where
+// `sums` are the prefix sums of one pass. Two steps can only share a frequency if
+// their prefix sums agree modulo the drift, so only neighbours within each residue
+// class need comparing and no cycle is ever simulated.
+pub fn first_repeat(changes: &[(usize, isize)]) -> Result<Option<FirstRepeat>, Error> {
+    let n = changes.len();
+    let mut sums: Vec<isize> = Vec::with_capacity(n);
+    let mut acc: isize = 0;
+    for &(line, delta) in changes {
+        sums.push(acc);
+        acc = acc.checked_add(delta).ok_or(Error::Overflow{line})?;
+    }
+    let drift = acc;
+
+    // Changes applied so far, as (cycle, index) where index 0 follows a complete pass
+    let repeat = |cycle: usize, index: usize, first_seen: usize, frequency: isize| {
+        let (cycle, position) = match index {
+            0 => (cycle - 1, n - 1),
+            _ => (cycle, index - 1),
+        };
+        FirstRepeat{frequency, cycle, position, line: changes[position].0, first_seen}
+    };
+
+    // A repeat within the first pass beats any later one
+    let mut seen: HashMap<isize, usize> = HashMap::new();
+    for (index, &sum) in sums.iter().enumerate() {
+        if let Some(&first_seen) = seen.get(&sum) {
+            return Ok(Some(repeat(0, index, first_seen, sum)));
+        }
+        seen.insert(sum, index);
+    }
+    if n == 0 {
+        return Ok(None);
+    }
+    if drift == 0 {
+        return Ok(Some(repeat(1, 0, 0, 0)));
+    }
+
+    let period = (drift as i128).abs();
+    let mut classes: HashMap<i128, Vec<(i128, usize)>> = HashMap::new();
+    for (index, &sum) in sums.iter().enumerate() {
+        classes.entry((sum as i128).rem_euclid(period)).or_default().push((sum as i128, index));
+    }
+
+    // Each frequency is reached again by whichever earlier sum the drift carries onto it soonest
+    let mut best: Option<(usize, usize, usize, i128)> = None;
+    for class in classes.values_mut() {
+        class.sort_unstable();
+        for pair in class.windows(2) {
+            let (low, high) = (pair[0], pair[1]);
+            let cycles = ((high.0 - low.0) / period) as usize;
+            let (index, first_seen, frequency) = match drift > 0 {
+                true => (low.1, high.1, high.0),
+                false => (high.1, low.1, low.0),
+            };
+            if best.is_none_or(|(c, i, _, _)| (cycles, index) < (c, i)) {
+                best = Some((cycles, index, first_seen, frequency));
+            }
+        }
+    }
+
+    Ok(best.map(|(cycle, index, first_seen, frequency)| repeat(cycle, index, first_seen, frequency as isize)))
+}
+
+// The frequency after one change
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TraceStep {
+    // 1-based count of changes applied
+    pub step: usize,
+    // 0-based pass over the list
+    pub cycle: usize,
+    pub line: usize,
+    pub delta: isize,
+    pub frequency: isize,
+}
+
+// Applies the changes over and over; ends early only if the frequency would overflow
+pub struct Trace<'a> {
+    changes: &'a [(usize, isize)],
+    step: usize,
+    frequency: isize,
+}
+
+pub fn trace(changes: &[(usize, isize)]) -> Trace<'_> {
+    Trace{changes, step: 0, frequency: 0}
+}
+
+impl<'a> Iterator for Trace<'a> {
+    type Item = TraceStep;
+
+    fn next(&mut self) -> Option<TraceStep> {
+        if self.changes.is_empty() {
+            return None;
+        }
+        let (line, delta) = self.changes[self.step % self.changes.len()];
+        let frequency = self.frequency.checked_add(delta)?;
+        let cycle = self.step / self.changes.len();
+        self.step += 1;
+        self.frequency = frequency;
+        Some(TraceStep{step: self.step, cycle, line, delta, frequency})
+    }
+}
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+// At most `width` characters, each the mean of an equal run of frequencies
+pub fn sparkline(frequencies: &[isize], width: usize) -> String {
+    if frequencies.is_empty() || width == 0 {
+        return String::new();
+    }
+    let bucket = frequencies.len().div_ceil(width);
+    let means: Vec<i128> = frequencies.chunks(bucket)
+        .map(|chunk| chunk.iter().map(|&f| f as i128).sum::<i128>() / chunk.len() as i128)
+        .collect();
+    let low = *means.iter().min().unwrap();
+    let high = *means.iter().max().unwrap();
+    means.iter()
+        .map(|&mean| match high - low {
+            0 => SPARKS[0],
+            range => SPARKS[((mean - low) * (SPARKS.len() as i128 - 1) / range) as usize],
+        })
+        .collect()
+}
+
+pub struct Day01a;
+
+impl Solver for Day01a {
+    fn day(&self) -> u8 { 1 }
+    fn part(&self) -> Part { Part::A }
+
+    fn solve(&self, input: &str) -> Result<Answer, Error> {
+        let mut sum: isize = 0;
+        for (line, delta) in frequency_changes(input)? {
+            sum = sum.checked_add(delta).ok_or(Error::Overflow{line})?;
+        }
+        Ok(Answer::from(sum))
+    }
+}
+
+pub struct Day01b;
+
+impl Solver for Day01b {
+    fn day(&self) -> u8 { 1 }
+    fn part(&self) -> Part { Part::B }
+
+    fn solve(&self, input: &str) -> Result<Answer, Error> {
+        let deltas = frequency_changes(input)?;
+        if deltas.is_empty() {
+            return Err(Error::NoAnswer("no frequency changes"));
+        }
+
+        match first_repeat(&deltas)? {
+            Some(repeat) => Ok(Answer::from(repeat.frequency)),
+            None => Err(Error::NoAnswer("frequency never repeats")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day01::*;
+
+    #[test]
+    fn test_frequency_changes_reports_line() {
+        let e = frequency_changes("+1\n-2\n\n+x\n").unwrap_err();
+        assert_eq!(Some(4), e.line());
+        assert_eq!("+x", e.text());
+    }
+
+    fn changes(deltas: &[isize]) -> Vec<(usize, isize)> {
+        deltas.iter().enumerate().map(|(idx, &delta)| (idx + 1, delta)).collect()
+    }
+
+    // Repeats the list until a frequency comes round again, the way the puzzle describes
+    fn simulate(changes: &[(usize, isize)], max_steps: usize) -> Option<(isize, usize)> {
+        let mut observed = std::collections::HashSet::new();
+        let mut acc = 0;
+        observed.insert(acc);
+        for (step, (_, delta)) in changes.iter().cycle().take(max_steps).enumerate() {
+            acc += delta;
+            if !observed.insert(acc) {
+                return Some((acc, step));
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_first_repeat_puzzle_examples() {
+        let test_vectors: Vec<(Vec<isize>, isize)> = vec![
+            (vec![1, -2, 3, 1], 2),
+            (vec![1, -1], 0),
+            (vec![3, 3, 4, -2, -4], 10),
+            (vec![-6, 3, 8, 5, -6], 5),
+            (vec![7, 7, -2, -7, -4], 14),
+        ];
+
+        for (deltas, expected) in test_vectors {
+            let repeat = first_repeat(&changes(&deltas)).unwrap().unwrap();
+            assert_eq!(expected, repeat.frequency, "{:?}", deltas);
+        }
+    }
+
+    #[test]
+    fn test_first_repeat_position() {
+        let repeat = first_repeat(&changes(&[1, -2, 3, 1])).unwrap().unwrap();
+        assert_eq!(FirstRepeat{frequency: 2, cycle: 1, position: 1, line: 2, first_seen: 3}, repeat);
+
+        let repeat = first_repeat(&changes(&[1, -1])).unwrap().unwrap();
+        assert_eq!((0, 1, 0), (repeat.cycle, repeat.position, repeat.first_seen));
+    }
+
+    #[test]
+    fn test_first_repeat_matches_simulation() {
+        let test_vectors: Vec<Vec<isize>> = vec![
+            vec![5],
+            vec![0],
+            vec![2, -3],
+            vec![-4, 1, 7],
+            vec![10, -3, -3, -3, 2],
+            vec![-10, 3, 3, 3, -2],
+            vec![100, -99, 3, -7, 2],
+        ];
+
+        for deltas in test_vectors {
+            let changes = changes(&deltas);
+            let expected = simulate(&changes, 100_000);
+            let repeat = first_repeat(&changes).unwrap();
+            let actual = repeat.as_ref().map(|r| (r.frequency, r.cycle * changes.len() + r.position));
+            assert_eq!(expected, actual, "{:?}", deltas);
+        }
+    }
+
+    #[test]
+    fn test_no_repeat() {
+        assert_eq!(None, first_repeat(&changes(&[1, 1])).unwrap());
+        assert_eq!(None, first_repeat(&[]).unwrap());
+        assert_eq!(Err(Error::NoAnswer("frequency never repeats")), Day01b.solve("+1\n+1\n"));
+    }
+
+    #[test]
+    fn test_frequency_change_separators() {
+        let expected = vec![(1, 1), (1, -2), (1, 3), (1, 1)];
+        assert_eq!(expected, frequency_changes("+1, -2, +3, +1").unwrap());
+        assert_eq!(expected, frequency_changes("+1 -2\t+3  +1\n").unwrap());
+        assert_eq!(expected, frequency_changes("+1,-2,+3,+1,").unwrap());
+
+        let lines = vec![(1, 1), (2, -2), (4, 3), (4, 1)];
+        assert_eq!(lines, frequency_changes("+1\r\n-2\r\n\r\n+3, +1\r\n").unwrap());
+    }
+
+    #[test]
+    fn test_puzzle_examples() {
+        let part_a: Vec<(&str, isize)> = vec![
+            ("+1, -2, +3, +1", 3),
+            ("+1, +1, +1", 3),
+            ("+1, +1, -2", 0),
+            ("-1, -2, -3", -6),
+        ];
+        for (input, expected) in part_a {
+            assert_eq!(Ok(Answer::from(expected)), Day01a.solve(input), "{}", input);
+        }
+
+        let part_b: Vec<(&str, isize)> = vec![
+            ("+1, -2, +3, +1", 2),
+            ("+1, -1", 0),
+            ("+3, +3, +4, -2, -4", 10),
+            ("-6, +3, +8, +5, -6", 5),
+            ("+7, +7, -2, -7, -4", 14),
+        ];
+        for (input, expected) in part_b {
+            assert_eq!(Ok(Answer::from(expected)), Day01b.solve(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        let changes = frequency_changes("+1, -2, +3, +1").unwrap();
+        let steps: Vec<TraceStep> = trace(&changes).take(6).collect();
+        let frequencies: Vec<isize> = steps.iter().map(|s| s.frequency).collect();
+        assert_eq!(vec![1, -1, 2, 3, 4, 2], frequencies);
+        assert_eq!(TraceStep{step: 6, cycle: 1, line: 1, delta: -2, frequency: 2}, steps[5]);
+
+        let overflowing = [(1, isize::MAX), (2, 1)];
+        assert_eq!(1, trace(&overflowing).count());
+        assert_eq!(0, trace(&[]).count());
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!("▁▃▅█", sparkline(&[0, 3, 6, 10], 10));
+        assert_eq!("▁█", sparkline(&[0, 2, 10, 12], 2));
+        assert_eq!("▁▁▁", sparkline(&[5, 5, 5], 3));
+        assert_eq!("", sparkline(&[], 3));
+    }
+
+    #[test]
+    fn test_sum_overflow() {
+        let input = format!("+1\n+{}\n", isize::MAX);
+        assert_eq!(Err(Error::Overflow{line: 2}), Day01a.solve(&input));
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use crate::parse;
+use crate::error::Error;
+use crate::solver::{Answer, Part, Solver};
+
+pub struct Day02a;
+
+impl Solver for Day02a {
+    fn day(&self) -> u8 { 2 }
+    fn part(&self) -> Part { Part::A }
+
+    fn solve(&self, input: &str) -> Result<Answer, Error> {
+        let counts: Vec<_> = input.split('\n')
+            .map(parse::count_repeated_characters)
+            .collect();
+
+        let twice = counts.iter()
+            .filter(|s| s.contains(&2))
+            .count();
+        let thrice = counts.iter()
+            .filter(|s| s.contains(&3))
+            .count();
+
+        let checksum = twice * thrice;
+        Ok(Answer::from(checksum))
+    }
+}
+
+// Every pair of equal-length IDs differing in exactly one position, as indices (i, j) with
+// i < j, in order. Checks each pair directly; kept to cross-check `one_off_pairs`.
+pub fn one_off_pairs_naive(ids: &[&str]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, a) in ids.iter().enumerate() {
+        for (j, b) in ids.iter().enumerate().skip(i + 1) {
+            if a.chars().count() == b.chars().count() && parse::differing_character_count(a, b) == 1 {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+const HASH_BASE: u64 = 0x100000001b3;
+
+// Same result as `one_off_pairs_naive` in O(n·L): each ID is hashed once per position with
+// that position left out, so IDs differing only there land in the same bucket. Prefix
+// hashes make each wildcard hash O(1); buckets are verified, so collisions are harmless.
+pub fn one_off_pairs(ids: &[&str]) -> Vec<(usize, usize)> {
+    let chars: Vec<Vec<char>> = ids.iter().map(|id| id.chars().collect()).collect();
+    let longest = chars.iter().map(Vec::len).max().unwrap_or(0);
+    let powers: Vec<u64> = std::iter::successors(Some(1u64), |p| Some(p.wrapping_mul(HASH_BASE)))
+        .take(longest + 1)
+        .collect();
+
+    // (length, wildcard position, hash without that position) -> IDs
+    let mut buckets: HashMap<(usize, usize, u64), Vec<usize>> = HashMap::new();
+    for (idx, id) in chars.iter().enumerate() {
+        let len = id.len();
+        let mut prefix = vec![0u64; len + 1];
+        for (i, &c) in id.iter().enumerate() {
+            prefix[i + 1] = prefix[i].wrapping_mul(HASH_BASE).wrapping_add(c as u64 + 1);
+        }
+        for pos in 0 .. len {
+            let tail = len - pos - 1;
+            let suffix = prefix[len].wrapping_sub(prefix[pos + 1].wrapping_mul(powers[tail]));
+            let hash = prefix[pos].wrapping_mul(powers[tail]).wrapping_add(suffix);
+            buckets.entry((len, pos, hash)).or_default().push(idx);
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for (&(_, pos, _), bucket) in &buckets {
+        for (n, &i) in bucket.iter().enumerate() {
+            for &j in &bucket[n + 1 ..] {
+                let (a, b) = (&chars[i], &chars[j]);
+                let differs_only_at_pos = a[pos] != b[pos]
+                    && a.iter().zip(b).enumerate().all(|(k, (x, y))| k == pos || x == y);
+                if differs_only_at_pos {
+                    pairs.push((i, j));
+                }
+            }
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+pub struct Day02b;
+
+impl Solver for Day02b {
+    fn day(&self) -> u8 { 2 }
+    fn part(&self) -> Part { Part::B }
+
+    fn solve(&self, input: &str) -> Result<Answer, Error> {
+        let ids: Vec<&str> = input.split('\n').collect();
+        let (a, b) = match one_off_pairs(&ids).first() {
+            Some(&(i, j)) => (ids[i], ids[j]),
+            None => return Err(Error::NoAnswer("no two box IDs differ by exactly one character")),
+        };
+
+        let common = parse::same_characters(a, b);
+        Ok(Answer::from(common))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day02::*;
+
+    #[test]
+    fn test_puzzle_example() {
+        let input = "abcde\nfghij\nklmno\npqrst\nfguij\naxcye\nwvxyz";
+        let ids: Vec<&str> = input.split('\n').collect();
+        assert_eq!(vec![(1, 4)], one_off_pairs(&ids));
+        assert_eq!(Ok(Answer::from("fgij")), Day02b.solve(input));
+    }
+
+    #[test]
+    fn test_all_pairs() {
+        let ids = vec!["abc", "abd", "xbd", "abc", "ab", "abcd", "äbc", "abd"];
+        let expected = vec![(0, 1), (0, 6), (0, 7), (1, 2), (1, 3), (2, 7), (3, 6), (3, 7)];
+        assert_eq!(expected, one_off_pairs_naive(&ids));
+        assert_eq!(expected, one_off_pairs(&ids));
+    }
+
+    #[test]
+    fn test_matches_naive_on_puzzle_input() {
+        let ids: Vec<&str> = crate::input::EmbeddedInput::get(2).unwrap().lines().collect();
+        assert_eq!(one_off_pairs_naive(&ids), one_off_pairs(&ids));
+    }
+
+    #[test]
+    fn test_no_pair() {
+        assert_eq!(Err(Error::NoAnswer("no two box IDs differ by exactly one character")), Day02b.solve("abc\nxyz"));
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+
+use crate::grid::Grid;
+use crate::parse::{self, FabricClaim};
+use crate::error::Error;
+use crate::solver::{Answer, Part, Solver};
+
+// Far beyond any real fabric, but small enough to allocate
+const MAX_FABRIC_BYTES: usize = 1 << 30;
+
+// Parsed claims with their 1-based lines
+fn claims(input: &str) -> Result<Vec<(usize, FabricClaim)>, Error> {
+    Ok(parse::numbered_lines(input)?)
+}
+
+// A grid covering every claim; a claim reaching past what can be addressed is an overflow on its line
+fn fabric<T: Clone>(claims: &[(usize, FabricClaim)], zero: T) -> Result<Grid<T>, Error> {
+    let (mut w, mut h) = (0, 0);
+    for &(line, claim) in claims {
+        let cw = claim.x.checked_add(claim.w).ok_or(Error::Overflow{line})?;
+        let ch = claim.y.checked_add(claim.h).ok_or(Error::Overflow{line})?;
+        if cw > w { w = cw; }
+        if ch > h { h = ch; }
+        if w.checked_mul(h).is_none() {
+            return Err(Error::Overflow{line});
+        }
+    }
+    let too_large = Error::NoAnswer("the fabric is too large");
+    match (w * h).checked_mul(std::mem::size_of::<T>()) {
+        Some(bytes) if bytes <= MAX_FABRIC_BYTES => Grid::try_new(w, h, zero).ok_or(too_large),
+        _ => Err(too_large),
+    }
+}
+
+pub struct Day03a;
+
+impl Solver for Day03a {
+    fn day(&self) -> u8 { 3 }
+    fn part(&self) -> Part { Part::A }
+
+    fn solve(&self, input: &str) -> Result<Answer, Error> {
+        let claims = claims(input)?;
+        let mut grid: Grid<u16> = fabric(&claims, 0)?;
+        for (_, claim) in &claims {
+            grid.draw_rectangle_clipped(claim.x, claim.y, claim.w, claim.h, |x| x + 1);
+        }
+
+        let overlaps = grid.into_iter()
+            .filter(|x| *x > 1)
+            .count();
+        Ok(Answer::from(overlaps))
+    }
+}
+
+pub struct Day03b;
+
+impl Solver for Day03b {
+    fn day(&self) -> u8 { 3 }
+    fn part(&self) -> Part { Part::B }
+
+    fn solve(&self, input: &str) -> Result<Answer, Error> {
+        let claims = claims(input)?;
+        let mut grid: Grid<HashSet<FabricClaim>> = fabric(&claims, HashSet::new())?;
+
+        // Paint the grid with *all* of the claims on each pixel
+        for (_, claim) in &claims {
+            grid.draw_rectangle_clipped(claim.x, claim.y, claim.w, claim.h, |pixel| {
+                let mut copy = pixel.clone();
+                copy.insert(*claim);
+                copy
+            });
+        }
+
+        // Identify claims which have been painted on the same pixel as other claims
+        let mut to_delete = HashSet::new();
+        for pixel in &grid {
+            if pixel.len() > 1 {
+                for claim in pixel {
+                    to_delete.insert(claim);
+                }
+            }
+        }
+
+        // Un-paint claims marked to be deleted
+        // This is an optimization over scanning the entire grid
+        let mut mutable_copy = grid.clone();
+        for claim in to_delete {
+            mutable_copy.draw_rectangle_clipped(claim.x, claim.y, claim.w, claim.h, |p| {
+                let mut copy = p.clone();
+                copy.remove(claim);
+                copy
+            });
+        }
+
+        // Grab claims which remain
+        let mut remaining: HashSet<FabricClaim> = HashSet::new();
+        for pixel in mutable_copy {
+            for claim in pixel {
+                remaining.insert(claim);
+            }
+        }
+
+        if remaining.len() > 1 {
+            return Err(Error::NoAnswer("more than one claim overlaps no other"));
+        }
+        let uncut = remaining.into_iter().next()
+            .ok_or(Error::NoAnswer("every claim overlaps another"))?;
+        Ok(Answer::from(uncut.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day03::*;
+
+    #[test]
+    fn test_claim_past_usize_is_an_error() {
+        let input = format!("#1 @ 1,1: 2x2\n\n#2 @ {},0: 2x2\n", usize::MAX);
+        assert_eq!(Err(Error::Overflow{line: 3}), Day03a.solve(&input));
+        assert_eq!(Err(Error::Overflow{line: 3}), Day03b.solve(&input));
+
+        let input = format!("#1 @ 0,0: {}x1\n#2 @ 0,0: 1x{}\n", usize::MAX / 2, usize::MAX / 2);
+        assert_eq!(Err(Error::Overflow{line: 2}), Day03a.solve(&input));
+    }
+
+    #[test]
+    fn test_huge_claim_is_an_error() {
+        let too_large = Err(Error::NoAnswer("the fabric is too large"));
+        assert_eq!(too_large, Day03a.solve("#1 @ 0,0: 5000000000000000000x1\n"));
+        assert_eq!(too_large, Day03a.solve("#1 @ 0,0: 100000x100000\n"));
+        assert_eq!(too_large, Day03b.solve("#1 @ 0,0: 100000x100000\n"));
+    }
+
+    #[test]
+    fn test_uncut_claim_must_be_unique() {
+        let overlapping = "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2\n";
+        assert_eq!(Ok(Answer::from(3usize)), Day03b.solve(overlapping));
+
+        let disjoint = "#1 @ 0,0: 1x1\n#2 @ 2,0: 1x1\n#3 @ 4,0: 1x1\n";
+        assert!(matches!(Day03b.solve(disjoint), Err(Error::NoAnswer(_))));
+
+        let all_overlap = "#1 @ 0,0: 2x2\n#2 @ 1,1: 2x2\n";
+        assert!(matches!(Day03b.solve(all_overlap), Err(Error::NoAnswer(_))));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::guard::{GuardLogs, Shift};
+use crate::error::Error;
+use crate::sleep::{SleepMatrix, Window};
+use crate::solver::{Answer, Part, Solver};
+use crate::strategy::{Choice, MostFrequentMinute, MostMinutesAsleep, SleepStrategy};
+
+// Every shift in the log, of which there must be at least one
+pub fn shifts(input: &str) -> Result<Vec<Shift>, Error> {
+    let logs = GuardLogs::parse(input)?;
+    let shifts = logs.shifts().map_err(Error::InvalidLog)?;
+    if shifts.is_empty() {
+        return Err(Error::NoAnswer("no guard begins a shift"));
+    }
+    Ok(shifts)
+}
+
+// One row per guard, one column per minute of the window
+pub fn sleep_schedule(input: &str, window: Window) -> Result<SleepMatrix, Error> {
+    Ok(SleepMatrix::from_shifts(&shifts(input)?, window))
+}
+
+// The choice's answer; an overflow is blamed on the chosen guard's first shift
+pub fn answer(choice: &Choice, shifts: &[Shift]) -> Result<isize, Error> {
+    choice.answer().ok_or_else(|| {
+        let line = shifts.iter()
+            .find(|shift| shift.guard == choice.guard)
+            .map_or(0, |shift| shift.line);
+        Error::Overflow{line}
+    })
+}
+
+pub fn solve_with(strategy: &dyn SleepStrategy, input: &str) -> Result<Answer, Error> {
+    let shifts = shifts(input)?;
+    let schedule = SleepMatrix::from_shifts(&shifts, Window::MidnightHour);
+    let choice = strategy.choose(&schedule).ok_or(Error::NoAnswer("no guard begins a shift"))?;
+    Ok(Answer::from(answer(&choice, &shifts)?))
+}
+
+pub struct Day04a;
+
+impl Solver for Day04a {
+    fn day(&self) -> u8 { 4 }
+    fn part(&self) -> Part { Part::A }
+
+    fn solve(&self, input: &str) -> Result<Answer, Error> {
+        solve_with(&MostMinutesAsleep, input)
+    }
+}
+
+pub struct Day04b;
+
+impl Solver for Day04b {
+    fn day(&self) -> u8 { 4 }
+    fn part(&self) -> Part { Part::B }
+
+    fn solve(&self, input: &str) -> Result<Answer, Error> {
+        solve_with(&MostFrequentMinute, input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day04::*;
+    use crate::guard::Diagnostic;
+
+    #[test]
+    fn test_inconsistent_log_is_an_error() {
+        let input = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:25] wakes up
+";
+        let e = Day04a.solve(input).unwrap_err();
+        assert_eq!(Error::InvalidLog(Diagnostic::WakeWithoutSleep{line: 2}), e);
+    }
+
+    #[test]
+    fn test_answer_overflow_is_an_error() {
+        let input = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:06] wakes up
+[1518-11-02 00:00] Guard #9000000000000000000 begins shift
+[1518-11-02 00:05] falls asleep
+[1518-11-02 00:25] wakes up
+";
+        assert_eq!(Err(Error::Overflow{line: 4}), Day04a.solve(input));
+        assert_eq!(Err(Error::Overflow{line: 4}), Day04b.solve(input));
+    }
+
+    #[test]
+    fn test_naps_past_the_midnight_hour() {
+        let input = "\
+[1518-11-01 23:50] Guard #10 begins shift
+[1518-11-02 00:30] falls asleep
+[1518-11-02 02:00] wakes up
+[1518-11-02 23:50] Guard #99 begins shift
+[1518-11-02 23:55] falls asleep
+[1518-11-03 00:45] wakes up
+";
+        let sleepiest = |window| {
+            let choice = MostMinutesAsleep.choose(&sleep_schedule(input, window).unwrap()).unwrap();
+            (choice.guard, choice.minute)
+        };
+        // Ties go to the later minute
+        assert_eq!((99, 44), sleepiest(Window::MidnightHour));
+        assert_eq!((10, 119), sleepiest(Window::FullDay));
+        assert_eq!((99, 9), sleepiest(Window::Custom{start: -10, minutes: 20}));
+    }
+}
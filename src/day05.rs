@@ -0,0 +1,116 @@
+use rayon::prelude::*;
+
+use crate::error::Error;
+use crate::polymer::Polymer;
+use crate::solver::{Answer, Part, Solver};
+
+pub use crate::polymer::react;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Sequential,
+    Parallel,
+}
+
+// Reacted length after removing each unit type, `a` through `z`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Improvements {
+    lengths: Vec<(char, usize)>,
+}
+
+impl Improvements {
+    // Reaction is order independent, so removing a type from the already reacted
+    // polymer and reacting again gives the same length as starting from scratch
+    pub fn search(reacted: &Polymer, mode: Mode) -> Improvements {
+        let remove = |kind: char| (kind, reacted.without(kind).react().len());
+        let lengths = match mode {
+            Mode::Sequential => ('a' ..= 'z').map(remove).collect(),
+            Mode::Parallel => ('a' ..= 'z').into_par_iter().map(remove).collect(),
+        };
+        Improvements{lengths}
+    }
+
+    pub fn length(&self, kind: char) -> Option<usize> {
+        self.lengths.iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, length)| *length)
+    }
+
+    // In alphabetical order
+    pub fn lengths(&self) -> &[(char, usize)] {
+        &self.lengths
+    }
+
+    // Shortest first, ties broken alphabetically
+    pub fn ranking(&self) -> Vec<(char, usize)> {
+        let mut ranking = self.lengths.clone();
+        ranking.sort_by_key(|&(kind, length)| (length, kind));
+        ranking
+    }
+
+    pub fn best(&self) -> (char, usize) {
+        self.ranking()[0]
+    }
+}
+
+// Returns the unit type whose removal gives the shortest polymer, and that length
+pub fn best_inhibitor(polymer: &Polymer) -> (char, usize) {
+    Improvements::search(&polymer.react(), Mode::Parallel).best()
+}
+
+pub struct Day05a;
+
+impl Solver for Day05a {
+    fn day(&self) -> u8 { 5 }
+    fn part(&self) -> Part { Part::A }
+
+    fn solve(&self, input: &str) -> Result<Answer, Error> {
+        let polymer: Polymer = input.parse()?;
+        Ok(Answer::from(polymer.react().len()))
+    }
+}
+
+pub struct Day05b;
+
+impl Solver for Day05b {
+    fn day(&self) -> u8 { 5 }
+    fn part(&self) -> Part { Part::B }
+
+    fn solve(&self, input: &str) -> Result<Answer, Error> {
+        let polymer: Polymer = input.parse()?;
+        let (_, shortest) = best_inhibitor(&polymer);
+        Ok(Answer::from(shortest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day05::*;
+
+    #[test]
+    fn test_best_inhibitor() {
+        let polymer: Polymer = "dabAcCaCBAcCcaDA\n".parse().unwrap();
+        assert_eq!(('c', 4), best_inhibitor(&polymer));
+    }
+
+    #[test]
+    fn test_improvements() {
+        let polymer: Polymer = "dabAcCaCBAcCcaDA".parse().unwrap();
+        let reacted = polymer.react();
+
+        let sequential = Improvements::search(&reacted, Mode::Sequential);
+        let parallel = Improvements::search(&reacted, Mode::Parallel);
+        assert_eq!(sequential, parallel);
+        assert_eq!(26, sequential.lengths().len());
+
+        let ranking = sequential.ranking();
+        assert_eq!(vec![('c', 4), ('a', 6), ('d', 6), ('b', 8), ('e', 10)], &ranking[.. 5]);
+        assert_eq!(Some(10), sequential.length('z'));
+        assert_eq!(None, sequential.length('A'));
+
+        // Matches removing each type from the unreacted polymer
+        for &(kind, length) in sequential.lengths() {
+            assert_eq!(polymer.without(kind).react().len(), length);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use crate::guard::Diagnostic;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    // The text does not have the expected shape
+    Malformed { line: Option<usize>, text: String, expected: &'static str },
+    // A numeric field could not be read as an integer
+    InvalidInteger { line: Option<usize>, text: String, expected: &'static str },
+    // A timestamp field could not be read as a date and time
+    InvalidTimestamp { line: Option<usize>, text: String, expected: &'static str },
+    // A guard log line describes an event that is not in the grammar
+    UnknownEvent { line: Option<usize>, text: String, expected: &'static str },
+    // A single character is not allowed at the given 1-based column
+    UnexpectedCharacter { line: Option<usize>, column: usize, text: String, expected: &'static str },
+}
+
+impl ParseError {
+    pub fn malformed(text: &str, expected: &'static str) -> ParseError {
+        ParseError::Malformed{line: None, text: text.to_string(), expected}
+    }
+
+    pub fn invalid_integer(text: &str, expected: &'static str) -> ParseError {
+        ParseError::InvalidInteger{line: None, text: text.to_string(), expected}
+    }
+
+    pub fn invalid_timestamp(text: &str, expected: &'static str) -> ParseError {
+        ParseError::InvalidTimestamp{line: None, text: text.to_string(), expected}
+    }
+
+    pub fn unknown_event(text: &str, expected: &'static str) -> ParseError {
+        ParseError::UnknownEvent{line: None, text: text.to_string(), expected}
+    }
+
+    pub fn unexpected_character(c: char, column: usize, expected: &'static str) -> ParseError {
+        ParseError::UnexpectedCharacter{line: None, column, text: c.to_string(), expected}
+    }
+
+    // 1-based line number within the input, if known
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            ParseError::Malformed{line, ..} => *line,
+            ParseError::InvalidInteger{line, ..} => *line,
+            ParseError::InvalidTimestamp{line, ..} => *line,
+            ParseError::UnknownEvent{line, ..} => *line,
+            ParseError::UnexpectedCharacter{line, ..} => *line,
+        }
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        match self {
+            ParseError::UnexpectedCharacter{column, ..} => Some(*column),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        match self {
+            ParseError::Malformed{text, ..} => text,
+            ParseError::InvalidInteger{text, ..} => text,
+            ParseError::InvalidTimestamp{text, ..} => text,
+            ParseError::UnknownEvent{text, ..} => text,
+            ParseError::UnexpectedCharacter{text, ..} => text,
+        }
+    }
+
+    pub fn expected(&self) -> &'static str {
+        match self {
+            ParseError::Malformed{expected, ..} => expected,
+            ParseError::InvalidInteger{expected, ..} => expected,
+            ParseError::InvalidTimestamp{expected, ..} => expected,
+            ParseError::UnknownEvent{expected, ..} => expected,
+            ParseError::UnexpectedCharacter{expected, ..} => expected,
+        }
+    }
+
+    pub fn at_line(mut self, n: usize) -> ParseError {
+        match self {
+            ParseError::Malformed{ref mut line, ..} => *line = Some(n),
+            ParseError::InvalidInteger{ref mut line, ..} => *line = Some(n),
+            ParseError::InvalidTimestamp{ref mut line, ..} => *line = Some(n),
+            ParseError::UnknownEvent{ref mut line, ..} => *line = Some(n),
+            ParseError::UnexpectedCharacter{ref mut line, ..} => *line = Some(n),
+        }
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.line(), self.column()) {
+            (Some(line), Some(column)) => write!(f, "line {}, column {}: ", line, column)?,
+            (Some(line), None) => write!(f, "line {}: ", line)?,
+            (None, Some(column)) => write!(f, "column {}: ", column)?,
+            (None, None) => (),
+        }
+        write!(f, "expected {}, found {:?}", self.expected(), self.text())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    Parse(ParseError),
+    // The input parsed but has no answer
+    NoAnswer(&'static str),
+    // Applying the value on the given 1-based line overflowed
+    Overflow { line: usize },
+    // The guard log parsed but does not describe a consistent sequence of shifts
+    InvalidLog(Diagnostic),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::NoAnswer(reason) => write!(f, "no answer: {}", reason),
+            Error::Overflow{line} => write!(f, "line {}: arithmetic overflow", line),
+            Error::InvalidLog(d) => write!(f, "{}", d),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Error {
+        Error::Parse(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::*;
+
+    #[test]
+    fn test_parse_error_display() {
+        let e = ParseError::invalid_integer("x1", "an unsigned integer");
+        assert_eq!("expected an unsigned integer, found \"x1\"", e.to_string());
+        assert_eq!("line 7: expected an unsigned integer, found \"x1\"", e.at_line(7).to_string());
+    }
+}
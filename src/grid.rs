@@ -0,0 +1,377 @@
+use std::fmt;
+use std::iter::{self, StepBy};
+use std::ops::{Index, IndexMut};
+use std::slice;
+
+use rayon::prelude::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub x: usize,
+    pub y: usize,
+    pub w: usize,
+    pub h: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{} rectangle at {},{} does not fit in a {}x{} grid",
+            self.w, self.h, self.x, self.y, self.width, self.height)
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
+
+const NEIGHBOUR_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1), (0, -1), (1, -1),
+    (-1,  0),          (1,  0),
+    (-1,  1), (0,  1), (1,  1),
+];
+
+// Row-major cells, addressed by (x, y)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    w: usize,
+    h: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T>
+where
+    T: Clone,
+{
+    // Panics if w*h overflows; see `try_new`
+    pub fn new(w: usize, h: usize, zero: T) -> Grid<T> {
+        Grid::try_new(w, h, zero).expect("grid size overflows usize")
+    }
+
+    // None if w*h overflows or the cells can't be allocated
+    pub fn try_new(w: usize, h: usize, zero: T) -> Option<Grid<T>> {
+        let len = w.checked_mul(h)?;
+        let mut cells = Vec::new();
+        cells.try_reserve_exact(len).ok()?;
+        cells.resize(len, zero);
+        Some(Grid{w, h, cells})
+    }
+}
+
+impl<T> Grid<T> {
+    // None unless there are exactly w*h cells
+    pub fn from_vec(w: usize, h: usize, cells: Vec<T>) -> Option<Grid<T>> {
+        match w.checked_mul(h) == Some(cells.len()) {
+            true => Some(Grid{w, h, cells}),
+            false => None,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.w
+    }
+
+    pub fn height(&self) -> usize {
+        self.h
+    }
+
+    fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        match x < self.w && y < self.h {
+            true => Some(y*self.w + x),
+            false => None,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.index_of(x, y).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.index_of(x, y).map(move |idx| &mut self.cells[idx])
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        match y < self.h {
+            true => Some(&self.cells[y*self.w .. (y+1)*self.w]),
+            false => None,
+        }
+    }
+
+    pub fn column(&self, x: usize) -> Option<StepBy<slice::Iter<'_, T>>> {
+        match x < self.w {
+            true => Some(self.column_from(x)),
+            false => None,
+        }
+    }
+
+    // The up to eight cells around (x, y), with their coordinates
+    pub fn neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let inside = self.index_of(x, y).is_some();
+        NEIGHBOUR_OFFSETS.iter()
+            .filter(move |_| inside)
+            .filter_map(move |&(dx, dy)| {
+                let nx = x.checked_add_signed(dx)?;
+                let ny = y.checked_add_signed(dy)?;
+                self.get(nx, ny).map(|cell| ((nx, ny), cell))
+            })
+    }
+
+    // A zero-width grid has no cells to chunk, but still has `h` empty rows
+    fn empty_rows(&self) -> usize {
+        match self.w {
+            0 => self.h,
+            _ => 0,
+        }
+    }
+
+    // Borrowed rows, top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        let empty = iter::repeat_with(<&[T]>::default).take(self.empty_rows());
+        self.cells.chunks(self.w.max(1)).chain(empty)
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        let empty = iter::repeat_with(<&mut [T]>::default).take(self.empty_rows());
+        self.cells.chunks_mut(self.w.max(1)).chain(empty)
+    }
+
+    // Every `w`th cell from `x`; empty when the grid has no rows
+    fn column_from(&self, x: usize) -> StepBy<slice::Iter<'_, T>> {
+        self.cells.get(x ..).unwrap_or(&[]).iter().step_by(self.w)
+    }
+
+    // Strided views into the cells, left to right
+    pub fn columns(&self) -> impl ExactSizeIterator<Item = StepBy<slice::Iter<'_, T>>> {
+        (0 .. self.w).map(move |x| self.column_from(x))
+    }
+
+    // Each column holds references into the grid, so no cells are copied
+    pub fn columns_mut(&mut self) -> std::vec::IntoIter<Vec<&mut T>> {
+        let w = self.w;
+        let mut columns: Vec<Vec<&mut T>> = (0 .. w)
+            .map(|_| Vec::with_capacity(self.h))
+            .collect();
+        for (idx, cell) in self.cells.iter_mut().enumerate() {
+            columns[idx % w].push(cell);
+        }
+        columns.into_iter()
+    }
+
+    // Row-major
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    // Leaves the grid untouched if any part of the rectangle is outside it
+    pub fn draw_rectangle<F>(&mut self, x: usize, y: usize, w: usize, h: usize, increment: F) -> Result<(), OutOfBounds>
+    where
+        F: Fn(&T) -> T,
+    {
+        let fits = x.checked_add(w).is_some_and(|right| right <= self.w)
+            && y.checked_add(h).is_some_and(|bottom| bottom <= self.h);
+        if !fits {
+            return Err(OutOfBounds{x, y, w, h, width: self.w, height: self.h});
+        }
+        self.draw_rectangle_clipped(x, y, w, h, increment);
+        Ok(())
+    }
+
+    // Draws only the part of the rectangle inside the grid
+    pub fn draw_rectangle_clipped<F>(&mut self, x: usize, y: usize, w: usize, h: usize, increment: F)
+    where
+        F: Fn(&T) -> T,
+    {
+        let right = x.saturating_add(w).min(self.w);
+        let bottom = y.saturating_add(h).min(self.h);
+        for y in y .. bottom {
+            let row = y * self.w;
+            for x in x .. right {
+                let idx = row + x;
+                let new_value = increment(&self.cells[idx]);
+                self.cells[idx] = new_value;
+            }
+        }
+    }
+}
+
+impl<T> Grid<T>
+where
+    T: Sync,
+{
+    pub fn par_rows(&self) -> impl IndexedParallelIterator<Item = &[T]> {
+        let empty = (0 .. self.empty_rows()).into_par_iter().map(|_| <&[T]>::default());
+        self.cells.par_chunks(self.w.max(1)).chain(empty)
+    }
+
+    pub fn par_columns(&self) -> impl IndexedParallelIterator<Item = StepBy<slice::Iter<'_, T>>> {
+        (0 .. self.w).into_par_iter()
+            .map(move |x| self.column_from(x))
+    }
+}
+
+impl<T> Grid<T>
+where
+    T: Send,
+{
+    pub fn par_rows_mut(&mut self) -> impl IndexedParallelIterator<Item = &mut [T]> {
+        let empty = (0 .. self.empty_rows()).into_par_iter().map(|_| <&mut [T]>::default());
+        self.cells.par_chunks_mut(self.w.max(1)).chain(empty)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        match self.get(x, y) {
+            Some(cell) => cell,
+            None => panic!("({}, {}) is outside a {}x{} grid", x, y, self.w, self.h),
+        }
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        let (w, h) = (self.w, self.h);
+        match self.get_mut(x, y) {
+            Some(cell) => cell,
+            None => panic!("({}, {}) is outside a {}x{} grid", x, y, w, h),
+        }
+    }
+}
+
+impl<T> IntoIterator for Grid<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> std::vec::IntoIter<T> {
+        self.cells.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Grid<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.cells.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::*;
+
+    fn numbered(w: usize, h: usize) -> Grid<usize> {
+        Grid::from_vec(w, h, (0 .. w*h).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_get() {
+        let mut grid = numbered(3, 2);
+        assert_eq!(Some(&5), grid.get(2, 1));
+        assert_eq!(None, grid.get(3, 0));
+        assert_eq!(None, grid.get(0, 2));
+
+        *grid.get_mut(1, 1).unwrap() = 40;
+        grid[(0, 0)] += 10;
+        assert_eq!(40, grid[(1, 1)]);
+        assert_eq!(10, grid[(0, 0)]);
+        assert!(Grid::from_vec(3, 2, vec![0; 5]).is_none());
+        assert!(Grid::from_vec(usize::MAX, 2, vec![0; 5]).is_none());
+        assert!(Grid::try_new(usize::MAX, 2, 0).is_none());
+        assert!(Grid::try_new(usize::MAX / 2, 1, 0u64).is_none());
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = numbered(3, 2);
+        assert_eq!(Some(&[3, 4, 5][..]), grid.row(1));
+        assert_eq!(None, grid.row(2));
+        assert_eq!(vec![&1, &4], grid.column(1).unwrap().collect::<Vec<_>>());
+        assert!(grid.column(3).is_none());
+    }
+
+    #[test]
+    fn test_row_and_column_iterators() {
+        let mut grid = numbered(3, 2);
+        let rows: Vec<&[usize]> = grid.rows().collect();
+        assert_eq!(vec![&[0, 1, 2][..], &[3, 4, 5][..]], rows);
+
+        let columns: Vec<Vec<usize>> = grid.columns()
+            .map(|column| column.copied().collect())
+            .collect();
+        assert_eq!(vec![vec![0, 3], vec![1, 4], vec![2, 5]], columns);
+
+        for row in grid.rows_mut() {
+            row[0] += 100;
+        }
+        for (x, column) in grid.columns_mut().enumerate() {
+            for cell in column {
+                *cell += 10 * x;
+            }
+        }
+        assert_eq!(Some(&[103, 14, 25][..]), grid.row(1));
+    }
+
+    #[test]
+    fn test_parallel_iterators() {
+        let mut grid = numbered(4, 3);
+        let row_sums: Vec<usize> = grid.par_rows().map(|row| row.iter().sum()).collect();
+        assert_eq!(vec![6, 22, 38], row_sums);
+
+        let column_sums: Vec<usize> = grid.par_columns().map(|column| column.sum()).collect();
+        assert_eq!(vec![12, 15, 18, 21], column_sums);
+
+        grid.par_rows_mut().for_each(|row| row.reverse());
+        assert_eq!(Some(&[7, 6, 5, 4][..]), grid.row(1));
+    }
+
+    #[test]
+    fn test_zero_height_columns() {
+        let mut grid = Grid::new(3, 0, 0u8);
+        assert_eq!(Some(0), grid.column(2).map(|column| column.count()));
+        assert_eq!(vec![0, 0, 0], grid.columns().map(|column| column.count()).collect::<Vec<_>>());
+        assert_eq!(vec![0, 0, 0], grid.par_columns().map(|column| column.count()).collect::<Vec<_>>());
+        assert_eq!(3, grid.columns_mut().filter(|column| column.is_empty()).count());
+    }
+
+    #[test]
+    fn test_zero_width_rows() {
+        let mut grid = Grid::new(0, 3, 0u8);
+        let empty: &[u8] = &[];
+        assert_eq!(Some(empty), grid.row(2));
+        assert_eq!(vec![empty; 3], grid.rows().collect::<Vec<_>>());
+        assert_eq!(vec![empty; 3], grid.par_rows().collect::<Vec<_>>());
+        assert_eq!(3, grid.rows_mut().filter(|row| row.is_empty()).count());
+        assert_eq!(3, grid.par_rows_mut().filter(|row| row.is_empty()).count());
+        assert_eq!(0, Grid::new(0, 0, 0u8).rows().count());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = numbered(3, 3);
+        let corner: Vec<usize> = grid.neighbours(0, 0).map(|(_, v)| *v).collect();
+        assert_eq!(vec![1, 3, 4], corner);
+        assert_eq!(8, grid.neighbours(1, 1).count());
+        assert_eq!(0, grid.neighbours(3, 3).count());
+    }
+
+    #[test]
+    fn test_draw_rectangle() {
+        let mut grid = Grid::new(4, 3, 0u8);
+        assert_eq!(Ok(()), grid.draw_rectangle(1, 1, 2, 2, |v| v + 1));
+        assert_eq!(4, grid.iter().filter(|v| **v == 1).count());
+
+        let e = grid.draw_rectangle(3, 0, 2, 1, |v| v + 1).unwrap_err();
+        assert_eq!(OutOfBounds{x: 3, y: 0, w: 2, h: 1, width: 4, height: 3}, e);
+        assert_eq!(4, grid.iter().sum::<u8>());
+
+        grid.draw_rectangle_clipped(3, 2, 5, 5, |v| v + 1);
+        assert_eq!(Some(&1), grid.get(3, 2));
+        assert_eq!(5, grid.iter().sum::<u8>());
+    }
+}
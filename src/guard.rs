@@ -0,0 +1,438 @@
+use std::fmt;
+use std::ops::Range;
+use std::slice;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::error::ParseError;
+use crate::parse::{self, GuardEvent, GuardLog};
+use crate::sleep::Nap;
+
+// A parsed log line and the 1-based line it came from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogEntry {
+    pub line: usize,
+    pub log: GuardLog,
+}
+
+// A line that repeats an earlier line's timestamp and event exactly
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Duplicate {
+    pub line: usize,
+    pub first_line: usize,
+    pub log: GuardLog,
+}
+
+impl fmt::Display for Duplicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {} duplicates line {}: {}", self.line, self.first_line, self.log)
+    }
+}
+
+// An inconsistency in a sorted guard log; lines are 1-based
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Diagnostic {
+    // Nobody is on duty yet
+    BeforeFirstShift { line: usize },
+    WakeWithoutSleep { line: usize },
+    DoubleSleep { line: usize, asleep_since: usize },
+    // A new guard arrives while the previous one is still asleep
+    ShiftChangeWhileAsleep { line: usize, asleep_since: usize },
+    // The log ends while the last guard is asleep
+    SleepLeftOpen { line: usize },
+}
+
+impl Diagnostic {
+    pub fn line(&self) -> usize {
+        match self {
+            Diagnostic::BeforeFirstShift{line} => *line,
+            Diagnostic::WakeWithoutSleep{line} => *line,
+            Diagnostic::DoubleSleep{line, ..} => *line,
+            Diagnostic::ShiftChangeWhileAsleep{line, ..} => *line,
+            Diagnostic::SleepLeftOpen{line} => *line,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::BeforeFirstShift{line} => {
+                write!(f, "line {}: event before any guard has begun a shift", line)
+            },
+            Diagnostic::WakeWithoutSleep{line} => {
+                write!(f, "line {}: guard wakes up without having fallen asleep", line)
+            },
+            Diagnostic::DoubleSleep{line, asleep_since} => {
+                write!(f, "line {}: guard falls asleep again, already asleep since line {}", line, asleep_since)
+            },
+            Diagnostic::ShiftChangeWhileAsleep{line, asleep_since} => {
+                write!(f, "line {}: shift changes while the previous guard is asleep since line {}", line, asleep_since)
+            },
+            Diagnostic::SleepLeftOpen{line} => {
+                write!(f, "line {}: guard falls asleep and never wakes up", line)
+            },
+        }
+    }
+}
+
+// One guard's night on duty. Minutes count from the midnight that starts the
+// night, so anything before midnight is negative.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shift {
+    pub guard: usize,
+    pub night: NaiveDate,
+    // Line of the `begins shift` entry
+    pub line: usize,
+    pub begins: i64,
+    pub naps: Vec<Range<i64>>,
+}
+
+impl Shift {
+    // Shifts starting in the evening belong to the following night
+    fn new(guard: usize, begins: NaiveDateTime, line: usize) -> Shift {
+        let night = match begins.time() >= NaiveTime::from_hms_opt(12, 0, 0).unwrap() {
+            true => begins.date() + Duration::days(1),
+            false => begins.date(),
+        };
+        let begins = (begins - night.and_time(NaiveTime::MIN)).num_minutes();
+        Shift{guard, night, line, begins, naps: Vec::new()}
+    }
+
+    fn minute(&self, ts: NaiveDateTime) -> i64 {
+        (ts - self.night.and_time(NaiveTime::MIN)).num_minutes()
+    }
+
+    pub fn minutes_asleep(&self) -> i64 {
+        self.naps.iter().map(|nap| nap.end - nap.start).sum()
+    }
+
+    pub fn is_asleep(&self, minute: i64) -> bool {
+        self.naps.iter().any(|nap| nap.contains(&minute))
+    }
+
+    // The naps as date-time intervals rather than minutes from midnight
+    pub fn absolute_naps(&self) -> impl Iterator<Item = Nap> + '_ {
+        let midnight = self.night.and_time(NaiveTime::MIN);
+        self.naps.iter().map(move |nap| Nap{
+            guard: self.guard,
+            time: midnight + Duration::minutes(nap.start) .. midnight + Duration::minutes(nap.end),
+        })
+    }
+}
+
+impl fmt::Display for Shift {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}  #{}  asleep {} minutes in {} naps",
+            self.night.format("%Y-%m-%d"), self.guard, self.minutes_asleep(), self.naps.len())
+    }
+}
+
+// Builds shifts one chronological entry at a time, for logs too large to hold at once
+#[derive(Clone, Debug, Default)]
+pub struct ShiftTracker {
+    current: Option<Shift>,
+    // When and on which line the guard on duty fell asleep
+    asleep: Option<(NaiveDateTime, usize)>,
+}
+
+impl ShiftTracker {
+    pub fn new() -> ShiftTracker {
+        ShiftTracker::default()
+    }
+
+    // Returns the previous shift once the next one begins
+    pub fn push(&mut self, entry: &LogEntry) -> Result<Option<Shift>, Diagnostic> {
+        let line = entry.line;
+        let ts = entry.log.ts;
+        match entry.log.e {
+            GuardEvent::BeginShift(guard) => {
+                if let Some((_, asleep_since)) = self.asleep {
+                    return Err(Diagnostic::ShiftChangeWhileAsleep{line, asleep_since});
+                }
+                Ok(self.current.replace(Shift::new(guard, ts, line)))
+            },
+            GuardEvent::FallAsleep => {
+                if self.current.is_none() {
+                    return Err(Diagnostic::BeforeFirstShift{line});
+                }
+                if let Some((_, asleep_since)) = self.asleep {
+                    return Err(Diagnostic::DoubleSleep{line, asleep_since});
+                }
+                self.asleep = Some((ts, line));
+                Ok(None)
+            },
+            GuardEvent::WakeUp => {
+                let shift = self.current.as_mut().ok_or(Diagnostic::BeforeFirstShift{line})?;
+                let (start, _) = self.asleep.take().ok_or(Diagnostic::WakeWithoutSleep{line})?;
+                let nap = shift.minute(start) .. shift.minute(ts);
+                shift.naps.push(nap);
+                Ok(None)
+            },
+        }
+    }
+
+    // The last shift, if any
+    pub fn finish(self) -> Result<Option<Shift>, Diagnostic> {
+        match self.asleep {
+            Some((_, line)) => Err(Diagnostic::SleepLeftOpen{line}),
+            None => Ok(self.current),
+        }
+    }
+}
+
+// Guard log entries in chronological order. Unlike a set, entries sharing a
+// timestamp are all kept; ties are ordered by event kind, then by line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GuardLogs {
+    entries: Vec<LogEntry>,
+}
+
+impl GuardLogs {
+    pub fn new(mut entries: Vec<LogEntry>) -> GuardLogs {
+        entries.sort_by(|a, b| a.log.cmp(&b.log).then(a.line.cmp(&b.line)));
+        GuardLogs{entries}
+    }
+
+    // Skips blank lines; errors carry their line number
+    pub fn parse(input: &str) -> Result<GuardLogs, ParseError> {
+        let entries = parse::numbered_lines::<GuardLog>(input)?
+            .into_iter()
+            .map(|(line, log)| LogEntry{line, log})
+            .collect();
+        Ok(GuardLogs::new(entries))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, LogEntry> {
+        self.entries.iter()
+    }
+
+    pub fn logs(&self) -> impl Iterator<Item = &GuardLog> {
+        self.entries.iter().map(|entry| &entry.log)
+    }
+
+    // Walks the log as a guard on duty would, in chronological order
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut on_duty = false;
+        let mut asleep_since: Option<usize> = None;
+
+        for entry in &self.entries {
+            let line = entry.line;
+            match entry.log.e {
+                GuardEvent::BeginShift(_) => {
+                    if let Some(asleep_since) = asleep_since.take() {
+                        diagnostics.push(Diagnostic::ShiftChangeWhileAsleep{line, asleep_since});
+                    }
+                    on_duty = true;
+                },
+                _ if !on_duty => diagnostics.push(Diagnostic::BeforeFirstShift{line}),
+                GuardEvent::FallAsleep => match asleep_since {
+                    Some(asleep_since) => diagnostics.push(Diagnostic::DoubleSleep{line, asleep_since}),
+                    None => asleep_since = Some(line),
+                },
+                GuardEvent::WakeUp => {
+                    if asleep_since.take().is_none() {
+                        diagnostics.push(Diagnostic::WakeWithoutSleep{line});
+                    }
+                },
+            }
+        }
+
+        if let Some(line) = asleep_since {
+            diagnostics.push(Diagnostic::SleepLeftOpen{line});
+        }
+        diagnostics
+    }
+
+    // Groups a consistent log into shifts; the first inconsistency is returned otherwise
+    pub fn shifts(&self) -> Result<Vec<Shift>, Diagnostic> {
+        let mut tracker = ShiftTracker::new();
+        let mut shifts = Vec::new();
+        for entry in &self.entries {
+            shifts.extend(tracker.push(entry)?);
+        }
+        shifts.extend(tracker.finish()?);
+        Ok(shifts)
+    }
+
+    // Identical entries end up adjacent once sorted
+    pub fn duplicates(&self) -> Vec<Duplicate> {
+        let mut duplicates = Vec::new();
+        let mut first: Option<&LogEntry> = None;
+        for entry in &self.entries {
+            match first {
+                Some(f) if f.log == entry.log => {
+                    duplicates.push(Duplicate{line: entry.line, first_line: f.line, log: entry.log.clone()});
+                },
+                _ => first = Some(entry),
+            }
+        }
+        duplicates
+    }
+}
+
+impl<'a> IntoIterator for &'a GuardLogs {
+    type Item = &'a LogEntry;
+    type IntoIter = slice::Iter<'a, LogEntry>;
+
+    fn into_iter(self) -> slice::Iter<'a, LogEntry> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::guard::*;
+    use crate::parse::GuardEvent;
+
+    // The example log from the puzzle, shared with the other guard log tests
+    pub(crate) const EXAMPLE: &str = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:55] wakes up
+";
+
+    #[test]
+    fn test_same_minute_entries_are_kept_in_order() {
+        let input = "\
+[1518-11-01 00:00] falls asleep
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:00] wakes up
+";
+        let logs = GuardLogs::parse(input).unwrap();
+        let events: Vec<(usize, GuardEvent)> = logs.iter()
+            .map(|entry| (entry.line, entry.log.e.clone()))
+            .collect();
+        assert_eq!(vec![
+            (3, GuardEvent::WakeUp),
+            (2, GuardEvent::BeginShift(10)),
+            (1, GuardEvent::FallAsleep),
+        ], events);
+        assert!(logs.duplicates().is_empty());
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let input = "\
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:00] Guard #10 begins shift
+
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:05] falls asleep
+";
+        let logs = GuardLogs::parse(input).unwrap();
+        assert_eq!(4, logs.len());
+
+        let duplicates: Vec<(usize, usize)> = logs.duplicates().into_iter()
+            .map(|d| (d.line, d.first_line))
+            .collect();
+        assert_eq!(vec![(4, 1), (5, 1)], duplicates);
+    }
+
+    #[test]
+    fn test_validate() {
+        let input = "\
+[1518-11-01 00:01] falls asleep
+[1518-11-01 00:02] Guard #10 begins shift
+[1518-11-01 00:03] wakes up
+[1518-11-01 00:04] falls asleep
+[1518-11-01 00:05] falls asleep
+[1518-11-02 00:00] Guard #99 begins shift
+[1518-11-02 00:10] falls asleep
+";
+        let logs = GuardLogs::parse(input).unwrap();
+        assert_eq!(vec![
+            Diagnostic::BeforeFirstShift{line: 1},
+            Diagnostic::WakeWithoutSleep{line: 3},
+            Diagnostic::DoubleSleep{line: 5, asleep_since: 4},
+            Diagnostic::ShiftChangeWhileAsleep{line: 6, asleep_since: 4},
+            Diagnostic::SleepLeftOpen{line: 7},
+        ], logs.validate());
+    }
+
+    #[test]
+    fn test_validate_puzzle_input() {
+        let logs = GuardLogs::parse(crate::input::EmbeddedInput::get(4).unwrap()).unwrap();
+        assert!(logs.validate().is_empty());
+    }
+
+    #[test]
+    fn test_shifts() {
+        let input = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-12-31 23:59] Guard #10 begins shift
+";
+        let shifts = GuardLogs::parse(input).unwrap().shifts().unwrap();
+        assert_eq!(3, shifts.len());
+
+        assert_eq!(NaiveDate::from_ymd_opt(1518, 11, 1).unwrap(), shifts[0].night);
+        assert_eq!(vec![5 .. 25, 30 .. 55], shifts[0].naps);
+        assert_eq!(45, shifts[0].minutes_asleep());
+
+        assert_eq!(99, shifts[1].guard);
+        assert_eq!(NaiveDate::from_ymd_opt(1518, 11, 2).unwrap(), shifts[1].night);
+        assert_eq!(-2, shifts[1].begins);
+        assert!(shifts[1].is_asleep(45));
+        assert!(!shifts[1].is_asleep(50));
+        assert_eq!("1518-11-02  #99  asleep 10 minutes in 1 naps", shifts[1].to_string());
+
+        assert_eq!(NaiveDate::from_ymd_opt(1519, 1, 1).unwrap(), shifts[2].night);
+    }
+
+    #[test]
+    fn test_example_shifts() {
+        let shifts = GuardLogs::parse(EXAMPLE).unwrap().shifts().unwrap();
+        let summary: Vec<(usize, i64)> = shifts.iter().map(|s| (s.guard, s.minutes_asleep())).collect();
+        assert_eq!(vec![(10, 45), (99, 10), (10, 5), (99, 10), (99, 10)], summary);
+    }
+
+    #[test]
+    fn test_shift_tracker_stops_at_first_problem() {
+        let input = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:06] falls asleep
+";
+        let logs = GuardLogs::parse(input).unwrap();
+        let mut tracker = ShiftTracker::new();
+        let results: Vec<_> = logs.iter().map(|entry| tracker.push(entry)).collect();
+        assert_eq!(vec![Ok(None), Ok(None), Err(Diagnostic::DoubleSleep{line: 3, asleep_since: 2})], results);
+        assert_eq!(Err(Diagnostic::DoubleSleep{line: 3, asleep_since: 2}), logs.shifts());
+    }
+
+    #[test]
+    fn test_parse_error_line() {
+        let e = GuardLogs::parse("[1518-11-01 00:00] Guard #10 begins shift\nnonsense\n").unwrap_err();
+        assert_eq!(Some(2), e.line());
+    }
+}
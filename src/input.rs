@@ -0,0 +1,245 @@
+use std::env;
+use std::fmt;
+use std::io::prelude::Read;
+use std::path::{Path, PathBuf};
+
+pub const INPUT_DIR_VAR: &str = "ADVENT2018_INPUT_DIR";
+
+#[derive(Debug)]
+pub enum InputError {
+    // No provider had an input for the day; lists every place that was tried
+    Missing { day: u8, searched: Vec<String> },
+    // An input exists but could not be read
+    Unreadable { path: PathBuf, error: std::io::Error },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Missing{day, searched} => {
+                write!(f, "no input for day {}, tried: {}", day, searched.join(", "))
+            },
+            InputError::Unreadable{path, error} => {
+                write!(f, "could not read {}: {}", path.display(), error)
+            },
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+// Puzzle inputs are shared by both parts of a day
+pub fn file_name(day: u8) -> String {
+    format!("{:02}a.txt", day)
+}
+
+pub trait InputProvider {
+    // Where this provider looks for the day's input, for error messages
+    fn describe(&self, day: u8) -> String;
+
+    // Ok(None) when this provider has nothing for the day
+    fn fetch(&self, day: u8) -> Result<Option<String>, InputError>;
+}
+
+fn read_file(path: &Path) -> Result<Option<String>, InputError> {
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(InputError::Unreadable{path: path.to_path_buf(), error}),
+    };
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .map_err(|error| InputError::Unreadable{path: path.to_path_buf(), error})?;
+    Ok(Some(contents))
+}
+
+// A single file, used whatever the day
+pub struct PathInput(pub PathBuf);
+
+impl InputProvider for PathInput {
+    fn describe(&self, _day: u8) -> String {
+        self.0.display().to_string()
+    }
+
+    fn fetch(&self, _day: u8) -> Result<Option<String>, InputError> {
+        read_file(&self.0)
+    }
+}
+
+// A directory of `01a.txt`, `02a.txt`, ...
+pub struct DirInput(pub PathBuf);
+
+impl InputProvider for DirInput {
+    fn describe(&self, day: u8) -> String {
+        self.0.join(file_name(day)).display().to_string()
+    }
+
+    fn fetch(&self, day: u8) -> Result<Option<String>, InputError> {
+        read_file(&self.0.join(file_name(day)))
+    }
+}
+
+// A directory named by the `ADVENT2018_INPUT_DIR` environment variable. Once the variable
+// is set, a day missing from the directory is an error rather than a reason to look elsewhere.
+pub struct EnvInput;
+
+impl InputProvider for EnvInput {
+    fn describe(&self, day: u8) -> String {
+        match env::var_os(INPUT_DIR_VAR) {
+            Some(dir) => format!("${} ({})", INPUT_DIR_VAR, DirInput(dir.into()).describe(day)),
+            None => format!("${} (unset)", INPUT_DIR_VAR),
+        }
+    }
+
+    fn fetch(&self, day: u8) -> Result<Option<String>, InputError> {
+        let dir = match env::var_os(INPUT_DIR_VAR) {
+            Some(dir) => DirInput(dir.into()),
+            None => return Ok(None),
+        };
+        match dir.fetch(day)? {
+            Some(contents) => Ok(Some(contents)),
+            None => Err(InputError::Missing{day, searched: vec![self.describe(day)]}),
+        }
+    }
+}
+
+// `advent2018` under the user's cache directory
+pub struct CacheInput;
+
+impl CacheInput {
+    pub fn dir() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CACHE_HOME").map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+        Some(base.join("advent2018"))
+    }
+}
+
+impl InputProvider for CacheInput {
+    fn describe(&self, day: u8) -> String {
+        match CacheInput::dir() {
+            Some(dir) => DirInput(dir).describe(day),
+            None => "cache directory (unknown)".to_string(),
+        }
+    }
+
+    fn fetch(&self, day: u8) -> Result<Option<String>, InputError> {
+        match CacheInput::dir() {
+            Some(dir) => DirInput(dir).fetch(day),
+            None => Ok(None),
+        }
+    }
+}
+
+// The inputs in `src/input`, compiled into the crate
+pub struct EmbeddedInput;
+
+impl EmbeddedInput {
+    pub fn get(day: u8) -> Option<&'static str> {
+        match day {
+            1 => Some(include_str!("input/01a.txt")),
+            2 => Some(include_str!("input/02a.txt")),
+            3 => Some(include_str!("input/03a.txt")),
+            4 => Some(include_str!("input/04a.txt")),
+            5 => Some(include_str!("input/05a.txt")),
+            _ => None,
+        }
+    }
+}
+
+impl InputProvider for EmbeddedInput {
+    fn describe(&self, day: u8) -> String {
+        format!("embedded {}", file_name(day))
+    }
+
+    fn fetch(&self, day: u8) -> Result<Option<String>, InputError> {
+        Ok(EmbeddedInput::get(day).map(|s| s.to_string()))
+    }
+}
+
+// Tries each provider in turn; the first one with an input wins
+#[derive(Default)]
+pub struct Inputs {
+    providers: Vec<Box<dyn InputProvider>>,
+}
+
+impl Inputs {
+    pub fn new() -> Inputs {
+        Inputs{providers: Vec::new()}
+    }
+
+    // Environment variable, then cache directory, then embedded inputs; see `EnvInput`
+    pub fn standard() -> Inputs {
+        Inputs::new()
+            .with(EnvInput)
+            .with(CacheInput)
+            .with(EmbeddedInput)
+    }
+
+    pub fn with<P>(mut self, provider: P) -> Inputs
+    where
+        P: InputProvider + 'static,
+    {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    pub fn load(&self, day: u8) -> Result<String, InputError> {
+        for provider in &self.providers {
+            if let Some(contents) = provider.fetch(day)? {
+                return Ok(contents);
+            }
+        }
+
+        let searched = self.providers.iter()
+            .map(|p| p.describe(day))
+            .collect();
+        Err(InputError::Missing{day, searched})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::*;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!("01a.txt", file_name(1));
+        assert_eq!("25a.txt", file_name(25));
+    }
+
+    #[test]
+    fn test_falls_through_to_embedded() {
+        let inputs = Inputs::new()
+            .with(DirInput(PathBuf::from("/nonexistent/advent2018")))
+            .with(EmbeddedInput);
+        assert_eq!(EmbeddedInput::get(2).unwrap(), inputs.load(2).unwrap());
+    }
+
+    #[test]
+    fn test_env_dir_is_not_a_fallback() {
+        let dir = env::temp_dir().join(format!("advent2018-inputs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(file_name(2)), "abcde\n").unwrap();
+        env::set_var(INPUT_DIR_VAR, &dir);
+
+        let inputs = Inputs::standard();
+        assert_eq!("abcde\n", inputs.load(2).unwrap());
+        let e = inputs.load(1).unwrap_err();
+        assert_eq!(
+            format!("no input for day 1, tried: ${} ({})", INPUT_DIR_VAR, dir.join("01a.txt").display()),
+            e.to_string());
+
+        env::remove_var(INPUT_DIR_VAR);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_lists_searched() {
+        let inputs = Inputs::new()
+            .with(PathInput(PathBuf::from("/nonexistent/input.txt")))
+            .with(EmbeddedInput);
+        let e = inputs.load(25).unwrap_err();
+        assert_eq!("no input for day 25, tried: /nonexistent/input.txt, embedded 25a.txt", e.to_string());
+    }
+}
@@ -0,0 +1,292 @@
+extern crate advent2018_rs;
+extern crate chrono;
+
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::io::prelude::Read;
+use std::process;
+use std::time::Instant;
+
+use chrono::NaiveDate;
+
+use advent2018_rs::{Part, Registry};
+use advent2018_rs::{day01, day04};
+use advent2018_rs::guard::GuardLogs;
+use advent2018_rs::input::{DirInput, Inputs, PathInput};
+use advent2018_rs::report::SleepReport;
+use advent2018_rs::sleep::{SleepMatrix, Window};
+use advent2018_rs::strategy;
+use advent2018_rs::stream::{self, InputOrder, Progress, StreamOptions};
+use advent2018_rs::timeline::{self, TimelineFilter};
+
+const USAGE: &str = "\
+usage: advent2018 <day> <part> [input]   solve one puzzle, reading input from a file or stdin (-)
+       advent2018 all [input-dir]        solve every puzzle, reading <input-dir>/<day>a.txt
+       advent2018 frequencies [--sparkline] [--cycles <n>] [input]
+                                         trace the Day 1 frequency as CSV or a sparkline,
+                                         up to its first repeat unless told how many cycles
+       advent2018 guards check [input]   list inconsistencies in a Day 4 guard log
+       advent2018 guards shifts [input]  summarise each night of a Day 4 guard log
+       advent2018 guards report [--csv] [input]
+                                         per-guard sleep statistics as a table or CSV
+       advent2018 guards strategies [input]
+                                         show the guard and minute each strategy picks
+       advent2018 guards stream [--sorted] [--chunk <lines>] [input]
+                                         report on a large guard log without loading it whole
+       advent2018 guards timeline [--guard <id>] [--from <date>] [--to <date>] [input]
+                                         chart each night minute by minute, dates as YYYY-MM-DD
+
+Without an explicit input, each day's input is read from $ADVENT2018_INPUT_DIR if it
+is set, otherwise the advent2018 cache directory, then the inputs built into the binary.";
+
+fn read_stdin() -> Result<String, String> {
+    let mut contents = String::new();
+    std::io::stdin().read_to_string(&mut contents)
+        .map_err(|e| format!("could not read stdin: {}", e))?;
+    Ok(contents)
+}
+
+fn load_input(day: u8, arg: Option<&str>) -> Result<String, String> {
+    let inputs = match arg {
+        Some("-") => return read_stdin(),
+        Some(path) => Inputs::new().with(PathInput(path.into())),
+        None => Inputs::standard(),
+    };
+    inputs.load(day).map_err(|e| e.to_string())
+}
+
+fn solve_one(registry: &Registry, args: &[String]) -> Result<(), String> {
+    let day: u8 = args[0].parse()
+        .map_err(|_| format!("invalid day {:?}", args[0]))?;
+    let part: Part = args.get(1).ok_or(USAGE)?.parse()?;
+
+    let solver = registry.get(day, part)
+        .ok_or(format!("no solver for day {} part {}", day, part))?;
+    let input = load_input(day, args.get(2).map(|s| s.as_str()))?;
+    let answer = solver.solve(&input).map_err(|e| e.to_string())?;
+    println!("{}", answer);
+    Ok(())
+}
+
+fn solve_all(registry: &Registry, args: &[String]) -> Result<(), String> {
+    let inputs = match args.first() {
+        Some(dir) => Inputs::new().with(DirInput(dir.into())),
+        None => Inputs::standard(),
+    };
+
+    println!("{:>3}  {:4}  {:>10}  answer", "day", "part", "ms");
+    let mut total_ms = 0.0;
+    let mut failures = 0;
+    for solver in registry.solvers() {
+        let input = inputs.load(solver.day()).map_err(|e| e.to_string())?;
+
+        let start = Instant::now();
+        let answer = solver.solve(&input);
+        let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+        total_ms += elapsed_ms;
+
+        let answer = match answer {
+            Ok(answer) => answer.to_string(),
+            Err(e) => {
+                failures += 1;
+                format!("error: {}", e)
+            },
+        };
+        println!("{:>3}  {:4}  {:>10.3}  {}", solver.day(), solver.part(), elapsed_ms, answer);
+    }
+    println!("{:>3}  {:4}  {:>10.3}  (total)", "", "", total_ms);
+
+    match failures {
+        0 => Ok(()),
+        n => Err(format!("{} puzzle(s) failed", n)),
+    }
+}
+
+fn trace_frequencies(args: &[String]) -> Result<(), String> {
+    let mut sparkline = false;
+    let mut cycles: Option<usize> = None;
+    let mut args = args;
+    while let Some(flag) = args.first().filter(|arg| arg.starts_with("--")) {
+        match flag.as_str() {
+            "--sparkline" => {
+                sparkline = true;
+                args = &args[1..];
+            },
+            "--cycles" => {
+                let value = args.get(1).ok_or(USAGE)?;
+                cycles = Some(value.parse().map_err(|_| format!("invalid cycle count {:?}", value))?);
+                args = &args[2..];
+            },
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let input = load_input(1, args.first().map(|s| s.as_str()))?;
+    let changes = day01::frequency_changes(&input).map_err(|e| e.to_string())?;
+    let steps = match cycles {
+        Some(cycles) => cycles.saturating_mul(changes.len()),
+        None => match day01::first_repeat(&changes).map_err(|e| e.to_string())? {
+            Some(repeat) => repeat.cycle * changes.len() + repeat.position + 1,
+            None => changes.len(),
+        },
+    };
+
+    let trace = day01::trace(&changes).take(steps);
+    if sparkline {
+        let frequencies: Vec<isize> = trace.map(|step| step.frequency).collect();
+        println!("{}", day01::sparkline(&frequencies, 80));
+    } else {
+        println!("step,cycle,line,delta,frequency");
+        for step in trace {
+            println!("{},{},{},{},{}", step.step, step.cycle, step.line, step.delta, step.frequency);
+        }
+    }
+    Ok(())
+}
+
+fn check_guards(args: &[String]) -> Result<(), String> {
+    let input = load_input(4, args.first().map(|s| s.as_str()))?;
+    let logs = GuardLogs::parse(&input).map_err(|e| e.to_string())?;
+
+    let duplicates = logs.duplicates();
+    let diagnostics = logs.validate();
+    for duplicate in &duplicates {
+        println!("{}", duplicate);
+    }
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+
+    match duplicates.len() + diagnostics.len() {
+        0 => Ok(()),
+        n => Err(format!("{} problem(s) in guard log", n)),
+    }
+}
+
+fn list_shifts(args: &[String]) -> Result<(), String> {
+    let input = load_input(4, args.first().map(|s| s.as_str()))?;
+    let logs = GuardLogs::parse(&input).map_err(|e| e.to_string())?;
+    for shift in logs.shifts().map_err(|d| d.to_string())? {
+        println!("{}", shift);
+    }
+    Ok(())
+}
+
+fn report_guards(args: &[String]) -> Result<(), String> {
+    let (csv, args) = match args.first().map(|s| s.as_str()) {
+        Some("--csv") => (true, &args[1..]),
+        _ => (false, args),
+    };
+    let input = load_input(4, args.first().map(|s| s.as_str()))?;
+    let logs = GuardLogs::parse(&input).map_err(|e| e.to_string())?;
+    let report = SleepReport::from_logs(&logs).map_err(|d| d.to_string())?;
+    match csv {
+        true => print!("{}", report.to_csv()),
+        false => print!("{}", report),
+    }
+    Ok(())
+}
+
+fn compare_strategies(args: &[String]) -> Result<(), String> {
+    let input = load_input(4, args.first().map(|s| s.as_str()))?;
+    let shifts = day04::shifts(&input).map_err(|e| e.to_string())?;
+    let schedule = SleepMatrix::from_shifts(&shifts, Window::MidnightHour);
+    for strategy in strategy::builtin() {
+        if let Some(choice) = strategy.choose(&schedule) {
+            let answer = day04::answer(&choice, &shifts).map_err(|e| e.to_string())?;
+            println!("{:<22}{:>8}  {}", strategy.name(), answer, choice);
+        }
+    }
+    Ok(())
+}
+
+fn stream_guards(args: &[String]) -> Result<(), String> {
+    let mut options = StreamOptions::new();
+    let mut args = args;
+    while let Some(flag) = args.first().filter(|arg| arg.starts_with("--")) {
+        match flag.as_str() {
+            "--sorted" => {
+                options = options.order(InputOrder::Sorted);
+                args = &args[1..];
+            },
+            "--chunk" => {
+                let value = args.get(1).ok_or(USAGE)?;
+                options = options.chunk_lines(value.parse().map_err(|_| format!("invalid chunk size {:?}", value))?);
+                args = &args[2..];
+            },
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let progress = |p| match p {
+        Progress::Read{lines} => eprintln!("read {} lines", lines),
+        Progress::Spilled{chunk, lines} => eprintln!("sorted chunk {} ({} lines)", chunk, lines),
+        Progress::Applied{entries} => eprintln!("applied {} entries", entries),
+    };
+    let matrix = match args.first().map(|s| s.as_str()) {
+        Some("-") => stream::ingest(std::io::stdin().lock(), &options, progress),
+        Some(path) => {
+            let file = File::open(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+            stream::ingest(BufReader::new(file), &options, progress)
+        },
+        None => stream::ingest(Cursor::new(load_input(4, None)?), &options, progress),
+    };
+    print!("{}", SleepReport::new(&matrix.map_err(|e| e.to_string())?));
+    Ok(())
+}
+
+fn show_timeline(args: &[String]) -> Result<(), String> {
+    let mut filter = TimelineFilter::new();
+    let mut args = args;
+    while let Some(flag) = args.first().filter(|arg| arg.starts_with("--")) {
+        let value = args.get(1).ok_or(USAGE)?;
+        let date = || NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| format!("invalid date {:?}", value));
+        filter = match flag.as_str() {
+            "--guard" => filter.guard(value.parse().map_err(|_| format!("invalid guard {:?}", value))?),
+            "--from" => filter.from(date()?),
+            "--to" => filter.to(date()?),
+            _ => return Err(USAGE.to_string()),
+        };
+        args = &args[2..];
+    }
+
+    let input = load_input(4, args.first().map(|s| s.as_str()))?;
+    let logs = GuardLogs::parse(&input).map_err(|e| e.to_string())?;
+    let shifts = logs.shifts().map_err(|d| d.to_string())?;
+    print!("{}", timeline::render(&shifts, Window::MidnightHour, &filter));
+    Ok(())
+}
+
+fn guards(args: &[String]) -> Result<(), String> {
+    match args.first().map(|s| s.as_str()) {
+        Some("check") => check_guards(&args[1..]),
+        Some("shifts") => list_shifts(&args[1..]),
+        Some("report") => report_guards(&args[1..]),
+        Some("strategies") => compare_strategies(&args[1..]),
+        Some("stream") => stream_guards(&args[1..]),
+        Some("timeline") => show_timeline(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let registry = Registry::builtin();
+
+    let result = match args.first().map(|s| s.as_str()) {
+        None | Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+        Some("all") => solve_all(&registry, &args[1..]),
+        Some("frequencies") => trace_frequencies(&args[1..]),
+        Some("guards") => guards(&args[1..]),
+        Some(_) => solve_one(&registry, &args),
+    };
+
+    if let Err(message) = result {
+        eprintln!("advent2018: {}", message);
+        process::exit(1);
+    }
+}
@@ -1,29 +1,30 @@
 use std::collections::{HashSet, HashMap};
 use std::cmp::Ordering;
+use std::num::IntErrorKind;
 use std::str::FromStr;
-use std::sync::{Once, ONCE_INIT};
+use std::sync::OnceLock;
 
+use crate::error::ParseError;
+
+// Lenient: anything that is not an integer reads as 0
 #[allow(dead_code)]
 pub fn signed_integer(src: &str) -> isize {
-    if src.to_owned().len() < 2 {
-        return 0;
-    }
-
-    let (sign_char, rest) = (*src).split_at(1);
-    let sign: isize = match sign_char {
-        "+" =>  1,
-        "-" => -1,
-        _ => return 0,
-    };
-    let unsigned = match isize::from_str(rest) {
-        Ok(v) => v,
-        Err(_) => return 0,
-    };
-    
-    sign * unsigned
+    checked_signed_integer(src).unwrap_or(0)
+}
+
+// Accepts `+7`, `-3` and `12`
+pub fn checked_signed_integer(src: &str) -> Result<isize, ParseError> {
+    isize::from_str(src).map_err(|e| {
+        let expected = match e.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => "an integer within isize range",
+            _ => "an integer like `+7`, `-3` or `12`",
+        };
+        ParseError::invalid_integer(src, expected)
+    })
 }
 
 #[allow(dead_code)]
+#[allow(clippy::iter_kv_map)]
 pub fn count_repeated_characters(src: &str) -> HashSet<usize> {
     let mut char_counts: HashMap<char, usize> = HashMap::new();
     for c in src.chars() {
@@ -51,6 +52,7 @@ pub fn same_characters(a: &str, b: &str) -> String {
 
 #[inline(always)]
 #[allow(dead_code)]
+#[allow(clippy::unnecessary_fold)]
 pub fn differing_character_count(a: &str, b: &str) -> usize {
     let a = a.chars();
     let b = b.chars();
@@ -76,34 +78,38 @@ impl FabricClaim {
         FabricClaim{id, x, y, w, h}
     }
 
-    pub fn from_str(spec: &str) -> FabricClaim {
+    pub fn area(&self) -> usize {
+        self.w * self.h
+    }
+}
+
+const FABRIC_CLAIM: &str = "a claim like `#123 @ 3,2: 5x4`";
+
+fn unsigned_field(field: &str, expected: &'static str) -> Result<usize, ParseError> {
+    let field = field.trim();
+    usize::from_str(field).map_err(|_| ParseError::invalid_integer(field, expected))
+}
+
+impl FromStr for FabricClaim {
+    type Err = ParseError;
+
+    fn from_str(spec: &str) -> Result<FabricClaim, ParseError> {
         // #123 @ 3,2: 5x4
-        let parts: Vec<&str> = spec.split(' ')
-            .filter(|part| part.to_string().len() > 1)
-            .collect();
-        
-        let mut id_chars = parts[0].chars();
-        id_chars.next();
-        let id = usize::from_str(&id_chars.as_str()).unwrap();
+        let malformed = || ParseError::malformed(spec, FABRIC_CLAIM);
 
-        let pos: Vec<&str> = parts[1].split(',')
-            .flat_map(|x| x.split_terminator(':'))
-            .filter(|x| x.to_string().len() > 0)
-            .collect();
-        let x = usize::from_str(&pos[0]).unwrap();
-        let y = usize::from_str(&pos[1]).unwrap();
+        let rest = spec.trim().strip_prefix('#').ok_or_else(malformed)?;
+        let (id, rest) = rest.split_once('@').ok_or_else(malformed)?;
+        let (pos, size) = rest.split_once(':').ok_or_else(malformed)?;
+        let (x, y) = pos.split_once(',').ok_or_else(malformed)?;
+        let (w, h) = size.split_once('x').ok_or_else(malformed)?;
 
-        let size: Vec<usize> = parts[2].split('x')
-            .map(|x| usize::from_str(x).unwrap())
-            .collect();
-        let w = size[0];
-        let h = size[1];
-        
-        FabricClaim::new(id, x, y, w, h)
-    }
+        let id = unsigned_field(id, "a claim id")?;
+        let x = unsigned_field(x, "a left edge offset")?;
+        let y = unsigned_field(y, "a top edge offset")?;
+        let w = unsigned_field(w, "a claim width")?;
+        let h = unsigned_field(h, "a claim height")?;
 
-    pub fn area(&self) -> usize {
-        self.w * self.h
+        Ok(FabricClaim::new(id, x, y, w, h))
     }
 }
 
@@ -113,7 +119,7 @@ impl std::fmt::Debug for FabricClaim {
     }
 }
 
-use time::{Tm, strptime};
+use chrono::{Duration, NaiveDateTime, Timelike};
 use regex::Regex;
 
 #[allow(dead_code)]
@@ -127,10 +133,33 @@ pub enum GuardEvent {
 #[allow(dead_code)]
 #[derive(Clone, PartialEq, Eq)]
 pub struct GuardLog {
-    pub ts: Tm,
+    pub ts: NaiveDateTime,
     pub e: GuardEvent,
 }
 
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+#[allow(dead_code)]
+impl GuardLog {
+    pub fn new(ts: NaiveDateTime, e: GuardEvent) -> GuardLog {
+        GuardLog{ts, e}
+    }
+
+    // Minute of the hour, 0-59
+    pub fn minute(&self) -> u32 {
+        self.ts.minute()
+    }
+
+    // Negative when `later` is actually earlier
+    pub fn duration_until(&self, later: &GuardLog) -> Duration {
+        later.ts - self.ts
+    }
+
+    pub fn minutes_until(&self, later: &GuardLog) -> i64 {
+        self.duration_until(later).num_minutes()
+    }
+}
+
 impl std::fmt::Debug for GuardLog {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let message = match self.e {
@@ -138,7 +167,7 @@ impl std::fmt::Debug for GuardLog {
             GuardEvent::FallAsleep => "fell asleep".to_string(),
             GuardEvent::WakeUp => "woke up".to_string(),
         };
-        write!(f, "[{}] {}", self.ts.strftime("%Y-%m-%d %H:%M").unwrap(), message)
+        write!(f, "[{}] {}", self.ts.format(TIMESTAMP_FORMAT), message)
     }
 }
 
@@ -148,55 +177,114 @@ impl PartialOrd for GuardLog {
     }
 }
 
+// Within the same minute, a wake up ends the previous guard's nap before a new
+// shift begins, and a guard can only fall asleep once their shift has begun
+impl GuardEvent {
+    fn rank(&self) -> (u8, usize) {
+        match self {
+            GuardEvent::WakeUp => (0, 0),
+            GuardEvent::BeginShift(id) => (1, *id),
+            GuardEvent::FallAsleep => (2, 0),
+        }
+    }
+}
+
 impl Ord for GuardLog {
     fn cmp(&self, other: &GuardLog) -> Ordering {
         self.ts.cmp(&other.ts)
+            .then_with(|| self.e.rank().cmp(&other.e.rank()))
     }
 }
 
-#[allow(dead_code)]
-static INIT_GUARDLOG_MATCHER: Once = ONCE_INIT;
-static mut GUARDLOG_MATCHER: Option<Regex> = None;
-static mut GUARDEVENT_MATCHER: Option<Regex> = None;
+static GUARDLOG_MATCHER: OnceLock<Regex> = OnceLock::new();
+static GUARDEVENT_MATCHER: OnceLock<Regex> = OnceLock::new();
+
+const GUARD_LOG: &str = "a log entry like `[1518-11-01 00:00] Guard #10 begins shift`";
+const GUARD_EVENT: &str = "`Guard #<id> begins shift`, `falls asleep` or `wakes up`";
+
+impl FromStr for GuardEvent {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<GuardEvent, ParseError> {
+        let gem = GUARDEVENT_MATCHER.get_or_init(|| Regex::new(r"^Guard #([0-9]+) begins shift$").unwrap());
+        match s {
+            "falls asleep" => Ok(GuardEvent::FallAsleep),
+            "wakes up" => Ok(GuardEvent::WakeUp),
+            _ => {
+                let cap = gem.captures(s)
+                    .ok_or_else(|| ParseError::unknown_event(s, GUARD_EVENT))?;
+                let id = usize::from_str(&cap[1])
+                    .map_err(|_| ParseError::invalid_integer(&cap[1], "a guard id"))?;
+                Ok(GuardEvent::BeginShift(id))
+            },
+        }
+    }
+}
 
-#[allow(dead_code)]
-impl GuardLog {
-    pub fn from_str(s: &str) -> GuardLog {
-        INIT_GUARDLOG_MATCHER.call_once(|| {
-            unsafe {
-                GUARDLOG_MATCHER = Some(Regex::new(r"^\[([0-9\- :]+)\] (.+)$").unwrap());
-                GUARDEVENT_MATCHER = Some(Regex::new(r"#([0-9]+)").unwrap());
-            }
-        });
+impl std::fmt::Display for GuardEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GuardEvent::BeginShift(id) => write!(f, "Guard #{} begins shift", id),
+            GuardEvent::FallAsleep => write!(f, "falls asleep"),
+            GuardEvent::WakeUp => write!(f, "wakes up"),
+        }
+    }
+}
+
+impl FromStr for GuardLog {
+    type Err = ParseError;
+
+    // Trailing whitespace, including a carriage return, is ignored
+    fn from_str(s: &str) -> Result<GuardLog, ParseError> {
         /*
         [1518-08-17 00:01] Guard #1021 begins shift
         [1518-03-16 00:39] falls asleep
         [1518-03-10 00:56] wakes up
         */
-        let glm = unsafe { GUARDLOG_MATCHER.clone().unwrap() };
-        let time_event = glm.captures(s).unwrap();
-        let gem = unsafe { GUARDEVENT_MATCHER.clone().unwrap() };
-        
-        let ts = strptime(&time_event[1], "%Y-%m-%d %H:%M").unwrap();
+        let s = s.trim_end();
+        let glm = GUARDLOG_MATCHER.get_or_init(|| Regex::new(r"^\[([0-9\- :]+)\] (.+)$").unwrap());
+        let time_event = glm.captures(s)
+            .ok_or_else(|| ParseError::malformed(s, GUARD_LOG))?;
         
-        let event = &time_event[2];
-        let e = match gem.captures(&event) {
-            Some(cap) => {
-                let id = usize::from_str(&cap[1]).unwrap();
-                GuardEvent::BeginShift(id)
-            },
-            None => {
-                match event {
-                    "wakes up" => GuardEvent::WakeUp,
-                    _ => GuardEvent::FallAsleep,
-                }
-            }
-        };
+        let ts = NaiveDateTime::parse_from_str(&time_event[1], TIMESTAMP_FORMAT)
+            .map_err(|_| ParseError::invalid_timestamp(&time_event[1], "a timestamp like `1518-11-01 00:00`"))?;
+        let e = time_event[2].parse()?;
 
-        GuardLog{ts, e}
+        Ok(GuardLog{ts, e})
     }
 }
 
+// Reproduces the puzzle's log line
+impl std::fmt::Display for GuardLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.ts.format(TIMESTAMP_FORMAT), self.e)
+    }
+}
+
+// Parses each non-blank line, tagging errors with their 1-based line number
+pub fn lines<T>(input: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr<Err = ParseError>,
+{
+    Ok(numbered_lines(input)?.into_iter().map(|(_, value)| value).collect())
+}
+
+// As `lines`, keeping each value's 1-based line number
+pub fn numbered_lines<T>(input: &str) -> Result<Vec<(usize, T)>, ParseError>
+where
+    T: FromStr<Err = ParseError>,
+{
+    input.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            T::from_str(line)
+                .map(|value| (idx + 1, value))
+                .map_err(|e| e.at_line(idx + 1))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::parse::*;
@@ -219,6 +307,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_checked_signed_integer() {
+        let test_vectors: Vec<(&str, Option<isize>)> = vec![
+            ("",      None),
+            ("+1",    Some(1)),
+            ("-44",   Some(-44)),
+            ("7",     Some(7)),
+            ("+0",    Some(0)),
+            ("+",     None),
+            ("-",     None),
+            ("abc",   None),
+            ("+-1",   None),
+            ("1 ",    None),
+            ("-9223372036854775808", Some(isize::MIN)),
+            ("+9223372036854775808", None),
+        ];
+
+        for (input, expected) in test_vectors {
+            assert_eq!(expected, checked_signed_integer(input).ok(), "{:?}", input);
+        }
+
+        let overflow = checked_signed_integer("+99999999999999999999").unwrap_err();
+        assert_eq!("an integer within isize range", overflow.expected());
+    }
+
     #[test]
     fn test_contains_repeated_characters() {
         // (input, contains_twice, contains_thrice)
@@ -240,6 +353,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::redundant_closure, clippy::unnecessary_fold, clippy::into_iter_on_ref)]
     fn test_contains_repeated_characters_checksum() {
         let lines = vec!["abcdef", "bababc", "abbcde", "abcccd", "aabcdd", "abcdee", "ababab"];
         let counts: Vec<HashSet<usize>> = lines.into_iter()
@@ -294,7 +408,22 @@ mod tests {
         ];
 
         for (input, expected) in test_vectors {
-            assert_eq!(expected, FabricClaim::from_str(input));
+            assert_eq!(Ok(expected), FabricClaim::from_str(input));
+        }
+    }
+
+    #[test]
+    fn test_fabric_claim_from_str_errors() {
+        let test_vectors: Vec<(&str, ParseError)> = vec![
+            ("", ParseError::malformed("", FABRIC_CLAIM)),
+            ("#1 @ 123,456", ParseError::malformed("#1 @ 123,456", FABRIC_CLAIM)),
+            ("#1 @ 123;456: 12x34", ParseError::malformed("#1 @ 123;456: 12x34", FABRIC_CLAIM)),
+            ("#a @ 123,456: 12x34", ParseError::invalid_integer("a", "a claim id")),
+            ("#1 @ 123,456: 12x-4", ParseError::invalid_integer("-4", "a claim height")),
+        ];
+
+        for (input, expected) in test_vectors {
+            assert_eq!(Err(expected), FabricClaim::from_str(input));
         }
     }
 
@@ -305,14 +434,99 @@ mod tests {
         ];
 
         for (input, expected) in test_vectors {
-            assert_eq!(expected, FabricClaim::from_str(input).area());
+            assert_eq!(expected, FabricClaim::from_str(input).unwrap().area());
         }
     }
 
     #[test]
     fn test_guard_log_from_str() {
-        println!("{:?}", GuardLog::from_str("[1518-08-17 00:01] Guard #1021 begins shift"));
-        println!("{:?}", GuardLog::from_str("[1518-03-16 00:39] falls asleep"));
-        println!("{:?}", GuardLog::from_str("[1518-03-10 00:56] wakes up"));
+        println!("{:?}", GuardLog::from_str("[1518-08-17 00:01] Guard #1021 begins shift").unwrap());
+        println!("{:?}", GuardLog::from_str("[1518-03-16 00:39] falls asleep").unwrap());
+        println!("{:?}", GuardLog::from_str("[1518-03-10 00:56] wakes up").unwrap());
+    }
+
+    #[test]
+    fn test_guard_log_round_trip() {
+        let test_vectors: Vec<&str> = vec![
+            "[1518-08-17 00:01] Guard #1021 begins shift",
+            "[1518-11-10 23:52] Guard #881 begins shift",
+            "[1518-03-16 00:39] falls asleep",
+            "[1518-03-10 00:56] wakes up",
+        ];
+
+        for line in test_vectors {
+            assert_eq!(line, GuardLog::from_str(line).unwrap().to_string());
+            assert_eq!(line, GuardLog::from_str(&format!("{} \r", line)).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn test_guard_event_from_str() {
+        let test_vectors: Vec<(&str, Option<GuardEvent>)> = vec![
+            ("Guard #10 begins shift", Some(GuardEvent::BeginShift(10))),
+            ("falls asleep",           Some(GuardEvent::FallAsleep)),
+            ("wakes up",               Some(GuardEvent::WakeUp)),
+            ("falls  asleep",          None),
+            ("Falls asleep",           None),
+            ("wakes",                  None),
+            ("Guard #10 ends shift",   None),
+            ("Guard 10 begins shift",  None),
+            ("dozes off",              None),
+        ];
+
+        for (input, expected) in test_vectors {
+            assert_eq!(expected, GuardEvent::from_str(input).ok(), "{:?}", input);
+        }
+
+        let e = GuardLog::from_str("[1518-03-16 00:39] dozes off").unwrap_err();
+        assert_eq!(ParseError::unknown_event("dozes off", GUARD_EVENT), e);
+    }
+
+    #[test]
+    fn test_guard_log_debug_format() {
+        let log = GuardLog::from_str("[1518-08-17 00:01] Guard #1021 begins shift").unwrap();
+        assert_eq!("[1518-08-17 00:01] #1021 began shift", format!("{:?}", log));
+        let log = GuardLog::from_str("[1518-03-16 00:39] falls asleep").unwrap();
+        assert_eq!("[1518-03-16 00:39] fell asleep", format!("{:?}", log));
+    }
+
+    #[test]
+    fn test_guard_log_minute_arithmetic() {
+        let begin = GuardLog::from_str("[1518-02-28 23:58] Guard #10 begins shift").unwrap();
+        let sleep = GuardLog::from_str("[1518-03-01 00:39] falls asleep").unwrap();
+
+        assert_eq!(39, sleep.minute());
+        assert_eq!(41, begin.minutes_until(&sleep));
+        assert_eq!(-41, sleep.minutes_until(&begin));
+        assert_eq!(Duration::minutes(41), begin.duration_until(&sleep));
+
+        let later = GuardLog::new(sleep.ts + Duration::minutes(30), GuardEvent::WakeUp);
+        assert_eq!("[1518-03-01 01:09] wakes up", later.to_string());
+    }
+
+    #[test]
+    fn test_guard_log_from_str_errors() {
+        let test_vectors: Vec<(&str, &str)> = vec![
+            ("1518-08-17 00:01 Guard #1021 begins shift", "1518-08-17 00:01 Guard #1021 begins shift"),
+            ("[1518-13-17 00:01] falls asleep", "1518-13-17 00:01"),
+            ("[1518-02-29 00:01] falls asleep", "1518-02-29 00:01"),
+            ("[1518-02-28 24:00] falls asleep", "1518-02-28 24:00"),
+            ("[1518-08-17 00:01] Guard #99999999999999999999999 begins shift", "99999999999999999999999"),
+        ];
+
+        for (input, offending) in test_vectors {
+            assert_eq!(offending, GuardLog::from_str(input).unwrap_err().text());
+        }
+    }
+
+    #[test]
+    fn test_lines_reports_line_number() {
+        let input = "#1 @ 1,3: 4x4\n\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2y2\n";
+        let e = lines::<FabricClaim>(input).unwrap_err();
+        assert_eq!(Some(4), e.line());
+        assert_eq!("#3 @ 5,5: 2y2", e.text());
+
+        let claims = lines::<FabricClaim>("#1 @ 1,3: 4x4\r\n#2 @ 3,1: 4x4\n").unwrap();
+        assert_eq!(2, claims.len());
     }
 }
\ No newline at end of file
@@ -0,0 +1,216 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::iter::FromIterator;
+use std::str::FromStr;
+
+use crate::error::ParseError;
+
+const POLYMER_UNIT: &str = "a unit letter a-z or A-Z";
+
+// Same letter, opposite case
+#[inline(always)]
+fn reacts(a: u8, b: u8) -> bool {
+    a ^ b == 0x20 && a.is_ascii_alphabetic()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Polarity {
+    Upper,
+    Lower,
+}
+
+// A single unit, stored as its ASCII letter
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Unit(u8);
+
+impl Unit {
+    // `kind` is the lowercase letter shared by both polarities
+    pub fn new(kind: char, polarity: Polarity) -> Option<Unit> {
+        if !kind.is_ascii_lowercase() {
+            return None;
+        }
+        let letter = kind as u8;
+        match polarity {
+            Polarity::Lower => Some(Unit(letter)),
+            Polarity::Upper => Some(Unit(letter.to_ascii_uppercase())),
+        }
+    }
+
+    pub fn from_byte(b: u8) -> Option<Unit> {
+        match b.is_ascii_alphabetic() {
+            true => Some(Unit(b)),
+            false => None,
+        }
+    }
+
+    pub fn kind(self) -> char {
+        self.0.to_ascii_lowercase() as char
+    }
+
+    pub fn polarity(self) -> Polarity {
+        match self.0.is_ascii_uppercase() {
+            true => Polarity::Upper,
+            false => Polarity::Lower,
+        }
+    }
+
+    // Same type, opposite polarity
+    pub fn reacts_with(self, other: Unit) -> bool {
+        reacts(self.0, other.0)
+    }
+
+    pub fn as_byte(self) -> u8 {
+        self.0
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0 as char)
+    }
+}
+
+// Single pass: each unit either annihilates the unit on top of the stack or is pushed
+pub fn react(polymer: &[u8]) -> Vec<u8> {
+    let mut stack: Vec<u8> = Vec::with_capacity(polymer.len());
+    for &unit in polymer {
+        match stack.last() {
+            Some(&top) if reacts(top, unit) => {
+                stack.pop();
+            },
+            _ => stack.push(unit),
+        }
+    }
+    stack
+}
+
+// Holds only ASCII letters
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Polymer(Vec<u8>);
+
+impl Polymer {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn units(&self) -> impl Iterator<Item = Unit> + '_ {
+        self.0.iter().map(|&b| Unit(b))
+    }
+
+    // The unit types present, as lowercase letters
+    pub fn kinds(&self) -> BTreeSet<char> {
+        self.units().map(Unit::kind).collect()
+    }
+
+    pub fn react(&self) -> Polymer {
+        Polymer(react(&self.0))
+    }
+
+    // Removes every unit of the given type, regardless of polarity
+    pub fn without(&self, kind: char) -> Polymer {
+        self.units()
+            .filter(|unit| unit.kind() != kind)
+            .collect()
+    }
+}
+
+impl FromIterator<Unit> for Polymer {
+    fn from_iter<I: IntoIterator<Item = Unit>>(iter: I) -> Polymer {
+        Polymer(iter.into_iter().map(Unit::as_byte).collect())
+    }
+}
+
+impl FromStr for Polymer {
+    type Err = ParseError;
+
+    // A single trailing line ending is allowed; any other non-letter is an error
+    fn from_str(s: &str) -> Result<Polymer, ParseError> {
+        let units = s.strip_suffix('\n')
+            .map(|s| s.strip_suffix('\r').unwrap_or(s))
+            .unwrap_or(s);
+
+        match units.bytes().position(|b| !b.is_ascii_alphabetic()) {
+            Some(idx) => {
+                let c = units[idx ..].chars().next().unwrap();
+                Err(ParseError::unexpected_character(c, idx + 1, POLYMER_UNIT))
+            },
+            None => Ok(Polymer(units.as_bytes().to_vec())),
+        }
+    }
+}
+
+impl fmt::Display for Polymer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let letters = std::str::from_utf8(&self.0).map_err(|_| fmt::Error)?;
+        f.write_str(letters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::polymer::*;
+
+    #[test]
+    fn test_unit_accessors() {
+        let a = Unit::from_byte(b'a').unwrap();
+        let upper_a = Unit::new('a', Polarity::Upper).unwrap();
+
+        assert_eq!('a', upper_a.kind());
+        assert_eq!(Polarity::Lower, a.polarity());
+        assert_eq!(Polarity::Upper, upper_a.polarity());
+        assert!(a.reacts_with(upper_a));
+        assert!(!a.reacts_with(a));
+        assert_eq!("A", upper_a.to_string());
+        assert_eq!(None, Unit::from_byte(b'@'));
+        assert_eq!(None, Unit::new('A', Polarity::Upper));
+    }
+
+    #[test]
+    fn test_react() {
+        let test_vectors: Vec<(&str, &str)> = vec![
+            ("aA", ""),
+            ("abBA", ""),
+            ("abAB", "abAB"),
+            ("aabAAB", "aabAAB"),
+            ("dabAcCaCBAcCcaDA", "dabCBAcaDA"),
+            ("a@`A", "a@`A"),
+        ];
+
+        for (input, expected) in test_vectors {
+            assert_eq!(expected.as_bytes(), &react(input.as_bytes())[..]);
+        }
+    }
+
+    #[test]
+    fn test_polymer_round_trip() {
+        let polymer: Polymer = "dabAcCaCBAcCcaDA\n".parse().unwrap();
+        assert_eq!("dabAcCaCBAcCcaDA", polymer.to_string());
+        assert_eq!("dabCBAcaDA", polymer.react().to_string());
+        assert_eq!("dbcCCBcCcD", polymer.without('a').to_string());
+        assert_eq!(vec!['a', 'b', 'c', 'd'], polymer.kinds().into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_polymer_from_str_errors() {
+        let test_vectors: Vec<(&str, usize, &str)> = vec![
+            ("ab\n\n", 3, "\n"),
+            ("ab cd", 3, " "),
+            ("\r", 1, "\r"),
+            ("abç", 3, "ç"),
+        ];
+
+        for (input, column, text) in test_vectors {
+            let e = Polymer::from_str(input).unwrap_err();
+            assert_eq!(Some(column), e.column());
+            assert_eq!(text, e.text());
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+use std::fmt::Write;
+
+use crate::guard::{Diagnostic, GuardLogs};
+use crate::sleep::{SleepMatrix, Window};
+use crate::strategy::busiest_minute;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuardReport {
+    pub guard: usize,
+    pub shifts: usize,
+    pub naps: usize,
+    // Whole naps, including any part outside the window
+    pub minutes_asleep: i64,
+    // Times asleep at each minute of the window
+    pub histogram: Vec<usize>,
+    // Minute from midnight and how often; None if never asleep in the window
+    pub busiest_minute: Option<(i64, usize)>,
+}
+
+impl GuardReport {
+    pub fn average_nap(&self) -> Option<f64> {
+        match self.naps {
+            0 => None,
+            naps => Some(self.minutes_asleep as f64 / naps as f64),
+        }
+    }
+
+    // One character per minute: `.` never asleep, 1-9, then `+` for ten or more
+    pub fn sparkline(&self) -> String {
+        self.histogram.iter()
+            .map(|&count| match count {
+                0 => '.',
+                1..=9 => (b'0' + count as u8) as char,
+                _ => '+',
+            })
+            .collect()
+    }
+}
+
+// Per-guard sleep statistics, in ascending guard order
+#[derive(Clone, Debug, PartialEq)]
+pub struct SleepReport {
+    window: Window,
+    guards: Vec<GuardReport>,
+}
+
+impl SleepReport {
+    pub fn new(matrix: &SleepMatrix) -> SleepReport {
+        let window = matrix.window();
+        let guards = matrix.rows()
+            .map(|(guard, row)| {
+                let naps = matrix.nap_stats(guard);
+                let busiest_minute = match busiest_minute(row) {
+                    (_, 0) => None,
+                    (column, count) => Some((window.minute(column), count)),
+                };
+                GuardReport{
+                    guard,
+                    shifts: matrix.shifts(guard),
+                    naps: naps.count,
+                    minutes_asleep: naps.minutes,
+                    histogram: row.to_vec(),
+                    busiest_minute,
+                }
+            })
+            .collect();
+        SleepReport{window, guards}
+    }
+
+    // Over the midnight hour, as in the puzzle
+    pub fn from_logs(logs: &GuardLogs) -> Result<SleepReport, Diagnostic> {
+        let shifts = logs.shifts()?;
+        Ok(SleepReport::new(&SleepMatrix::from_shifts(&shifts, Window::MidnightHour)))
+    }
+
+    pub fn window(&self) -> Window {
+        self.window
+    }
+
+    pub fn guards(&self) -> &[GuardReport] {
+        &self.guards
+    }
+
+    pub fn guard(&self, guard: usize) -> Option<&GuardReport> {
+        self.guards.iter().find(|report| report.guard == guard)
+    }
+
+    // A header row, then one row per guard with a column per minute of the window
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("guard,shifts,naps,minutes_asleep,average_nap,busiest_minute,frequency");
+        for column in 0 .. self.window.len() {
+            write!(csv, ",minute_{}", self.window.minute(column)).unwrap();
+        }
+        csv.push('\n');
+
+        for report in &self.guards {
+            write!(csv, "{},{},{},{}", report.guard, report.shifts, report.naps, report.minutes_asleep).unwrap();
+            match report.average_nap() {
+                Some(average) => write!(csv, ",{:.2}", average).unwrap(),
+                None => csv.push(','),
+            }
+            match report.busiest_minute {
+                Some((minute, frequency)) => write!(csv, ",{},{}", minute, frequency).unwrap(),
+                None => csv.push_str(",,0"),
+            }
+            for count in &report.histogram {
+                write!(csv, ",{}", count).unwrap();
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+impl fmt::Display for SleepReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>6}  {:>6}  {:>5}  {:>7}  {:>7}  {:>6}  {:>5}  histogram",
+            "guard", "shifts", "naps", "asleep", "avg nap", "minute", "times")?;
+        for report in &self.guards {
+            let average = match report.average_nap() {
+                Some(average) => format!("{:.1}", average),
+                None => "-".to_string(),
+            };
+            let (minute, frequency) = match report.busiest_minute {
+                Some((minute, frequency)) => (minute.to_string(), frequency),
+                None => ("-".to_string(), 0),
+            };
+            writeln!(f, "{:>6}  {:>6}  {:>5}  {:>7}  {:>7}  {:>6}  {:>5}  {}",
+                format!("#{}", report.guard), report.shifts, report.naps, report.minutes_asleep,
+                average, minute, frequency, report.sparkline())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::guard::tests::EXAMPLE;
+    use crate::report::*;
+
+    fn example() -> SleepReport {
+        // Plus a guard who never sleeps
+        let input = format!("{}[1518-11-06 00:01] Guard #7 begins shift\n", EXAMPLE);
+        SleepReport::from_logs(&GuardLogs::parse(&input).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_guard_statistics() {
+        let report = example();
+        assert_eq!(vec![7, 10, 99], report.guards().iter().map(|r| r.guard).collect::<Vec<_>>());
+
+        let ten = report.guard(10).unwrap();
+        assert_eq!((2, 3, 50), (ten.shifts, ten.naps, ten.minutes_asleep));
+        assert_eq!(Some(50.0 / 3.0), ten.average_nap());
+        assert_eq!(Some((24, 2)), ten.busiest_minute);
+        assert_eq!(60, ten.histogram.len());
+        assert_eq!(".....111111111111111111121111.1111111111111111111111111.....", ten.sparkline());
+
+        let seven = report.guard(7).unwrap();
+        assert_eq!((None, None), (seven.average_nap(), seven.busiest_minute));
+    }
+
+    #[test]
+    fn test_text_table() {
+        let table = example().to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(4, lines.len());
+        assert!(lines[0].starts_with(" guard  shifts   naps   asleep  avg nap  minute  times  histogram"));
+        assert!(lines[1].starts_with("    #7       1      0        0        -       -      0  ....."));
+        assert!(lines[3].starts_with("   #99       3      3       30     10.0      45      3  "));
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = example().to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].starts_with("guard,shifts,naps,minutes_asleep,average_nap,busiest_minute,frequency,minute_0,minute_1,"));
+        assert!(lines[0].ends_with(",minute_59"));
+        assert!(lines[1].starts_with("7,1,0,0,,,0,0,0,"));
+        assert!(lines[2].starts_with("10,2,3,50,16.67,24,2,0,0,0,0,0,1,"));
+        assert_eq!(7 + 60, lines[2].split(',').count());
+    }
+}
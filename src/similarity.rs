@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, HashMap};
+use std::iter::FromIterator;
+
+use crate::parse::differing_character_count;
+
+// Hamming distance between box IDs, as `differing_character_count` counts it. Like
+// Day 2, IDs of different lengths are never compared, so there is no distance between them.
+pub fn distance(a: &str, b: &str) -> Option<usize> {
+    match a.chars().count() == b.chars().count() {
+        true => Some(differing_character_count(a, b)),
+        false => None,
+    }
+}
+
+struct Node {
+    // Child node for each distance from this node's ID
+    children: BTreeMap<usize, usize>,
+}
+
+// A BK-tree over box IDs for each ID length. Node `i` holds the `i`th ID inserted, so IDs
+// are referred to by index.
+#[derive(Default)]
+pub struct SimilarityIndex {
+    ids: Vec<String>,
+    nodes: Vec<Node>,
+    // Root node of the tree for each length, in chars
+    roots: HashMap<usize, usize>,
+}
+
+impl SimilarityIndex {
+    pub fn new() -> SimilarityIndex {
+        SimilarityIndex::default()
+    }
+
+    pub fn insert(&mut self, id: &str) -> usize {
+        let idx = self.ids.len();
+        self.ids.push(id.to_string());
+        self.nodes.push(Node{children: BTreeMap::new()});
+
+        let mut node = *self.roots.entry(id.chars().count()).or_insert(idx);
+        while node != idx {
+            let d = differing_character_count(id, &self.ids[node]);
+            node = *self.nodes[node].children.entry(d).or_insert(idx);
+        }
+        idx
+    }
+
+    // The root of the tree holding IDs the same length as `query`
+    fn root(&self, query: &str) -> Option<usize> {
+        self.roots.get(&query.chars().count()).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&str> {
+        self.ids.get(idx).map(|id| id.as_str())
+    }
+
+    // IDs the same length as `query` and within `k` of it, as (index, distance) ordered
+    // by distance then index
+    pub fn within(&self, query: &str, k: usize) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = self.root(query).into_iter().collect();
+        while let Some(node) = stack.pop() {
+            let d = differing_character_count(query, &self.ids[node]);
+            if d <= k {
+                found.push((node, d));
+            }
+            // The triangle inequality rules out every other subtree
+            let range = d.saturating_sub(k) ..= d + k;
+            stack.extend(self.nodes[node].children.range(range).map(|(_, &child)| child));
+        }
+        found.sort_unstable_by_key(|&(idx, d)| (d, idx));
+        found
+    }
+
+    // The closest ID the same length as `query` as (index, distance); ties go to the earlier index
+    pub fn nearest(&self, query: &str) -> Option<(usize, usize)> {
+        self.nearest_except(query, None)
+    }
+
+    // The closest ID to the indexed ID `idx`, other than itself
+    pub fn nearest_to(&self, idx: usize) -> Option<(usize, usize)> {
+        let query = self.ids.get(idx)?;
+        self.nearest_except(query, Some(idx))
+    }
+
+    fn nearest_except(&self, query: &str, except: Option<usize>) -> Option<(usize, usize)> {
+        let mut best: Option<(usize, usize)> = None;
+        let mut stack: Vec<usize> = self.root(query).into_iter().collect();
+        while let Some(node) = stack.pop() {
+            let d = differing_character_count(query, &self.ids[node]);
+            let better = best.is_none_or(|(best_idx, best_d)| (d, node) < (best_d, best_idx));
+            if Some(node) != except && better {
+                best = Some((node, d));
+            }
+            let children = &self.nodes[node].children;
+            match best {
+                Some((_, best_d)) => {
+                    stack.extend(children.range(d.saturating_sub(best_d) ..= d + best_d).map(|(_, &child)| child));
+                },
+                None => stack.extend(children.values()),
+            }
+        }
+        best
+    }
+
+    // Every pair of IDs within `k` of each other, as (i, j, distance) with i < j, in order
+    pub fn pairs_within(&self, k: usize) -> Vec<(usize, usize, usize)> {
+        let mut pairs: Vec<(usize, usize, usize)> = self.ids.iter()
+            .enumerate()
+            .flat_map(|(i, id)| {
+                self.within(id, k).into_iter()
+                    .filter(move |&(j, _)| j > i)
+                    .map(move |(j, d)| (i, j, d))
+            })
+            .collect();
+        pairs.sort_unstable();
+        pairs
+    }
+
+    // Groups IDs linked by chains of IDs each within `k` of the next. Each cluster
+    // is in index order, and clusters are ordered by their first index.
+    pub fn clusters(&self, k: usize) -> Vec<Vec<usize>> {
+        let mut cluster_of: Vec<Option<usize>> = vec![None; self.ids.len()];
+        let mut clusters: Vec<Vec<usize>> = Vec::new();
+        for start in 0 .. self.ids.len() {
+            if cluster_of[start].is_some() {
+                continue;
+            }
+            let mut members = vec![start];
+            cluster_of[start] = Some(clusters.len());
+            let mut queue = vec![start];
+            while let Some(idx) = queue.pop() {
+                for (other, _) in self.within(&self.ids[idx], k) {
+                    if cluster_of[other].is_none() {
+                        cluster_of[other] = Some(clusters.len());
+                        members.push(other);
+                        queue.push(other);
+                    }
+                }
+            }
+            members.sort_unstable();
+            clusters.push(members);
+        }
+        clusters
+    }
+}
+
+impl<'a> FromIterator<&'a str> for SimilarityIndex {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> SimilarityIndex {
+        let mut index = SimilarityIndex::new();
+        for id in iter {
+            index.insert(id);
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::similarity::*;
+
+    const EXAMPLE: [&str; 7] = ["abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz"];
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(Some(0), distance("abc", "abc"));
+        assert_eq!(Some(2), distance("abcde", "axcye"));
+        assert_eq!(None, distance("abc", "ab"));
+        assert_eq!(None, distance("", "abc"));
+        assert_eq!(Some(0), distance("", ""));
+        assert_eq!(Some(1), distance("äbc", "abc"));
+    }
+
+    #[test]
+    fn test_within() {
+        let index: SimilarityIndex = EXAMPLE.iter().copied().collect();
+        assert_eq!(vec![(1, 0), (4, 1)], index.within("fghij", 1));
+        assert_eq!(vec![(0, 0), (5, 2)], index.within("abcde", 2));
+        assert!(SimilarityIndex::new().within("abc", 3).is_empty());
+    }
+
+    #[test]
+    fn test_nearest() {
+        let index: SimilarityIndex = EXAMPLE.iter().copied().collect();
+        assert_eq!(Some((1, 0)), index.nearest("fghij"));
+        assert_eq!(Some((4, 1)), index.nearest_to(1));
+        assert_eq!(Some((5, 2)), index.nearest_to(0));
+        assert_eq!(Some((0, 1)), index.nearest("abcdz"));
+        assert_eq!(None, SimilarityIndex::new().nearest("abc"));
+        assert_eq!(None, index.nearest_to(99));
+    }
+
+    #[test]
+    fn test_pairs_and_clusters() {
+        let index: SimilarityIndex = EXAMPLE.iter().copied().collect();
+        assert_eq!(vec![(1, 4, 1)], index.pairs_within(1));
+        assert_eq!(vec![(0, 5, 2), (1, 4, 1)], index.pairs_within(2));
+        assert_eq!(vec![vec![0, 5], vec![1, 4], vec![2], vec![3], vec![6]], index.clusters(2));
+
+        let chain: SimilarityIndex = vec!["aaa", "aab", "abb", "bbb", "zzz", "aaa"].into_iter().collect();
+        assert_eq!(vec![vec![0, 1, 2, 3, 5], vec![4]], chain.clusters(1));
+        assert_eq!(Some((5, 0)), chain.nearest_to(0));
+    }
+
+    #[test]
+    fn test_lengths_never_mix() {
+        let ids = ["abc", "ab", "abd", "ac", "xbc", "", "abcd", "b"];
+        let index: SimilarityIndex = ids.iter().copied().collect();
+        assert_eq!(vec![(0, 0), (2, 1), (4, 1)], index.within("abc", 1));
+        assert_eq!(vec![(1, 0), (3, 1)], index.within("ab", 5));
+
+        let pairs: Vec<(usize, usize)> = index.pairs_within(1).into_iter().map(|(i, j, _)| (i, j)).collect();
+        assert_eq!(vec![(0, 2), (0, 4), (1, 3)], pairs);
+        assert_eq!(crate::day02::one_off_pairs(&ids), pairs);
+
+        assert_eq!(None, index.nearest("abcde"));
+        assert_eq!(None, index.nearest_to(6));
+        assert_eq!(Some((5, 0)), index.nearest(""));
+        assert_eq!(vec![vec![0, 2, 4], vec![1, 3], vec![5], vec![6], vec![7]], index.clusters(1));
+    }
+
+    #[test]
+    fn test_matches_wildcard_search_on_puzzle_input() {
+        let ids: Vec<&str> = crate::input::EmbeddedInput::get(2).unwrap().lines().collect();
+        let index: SimilarityIndex = ids.iter().copied().collect();
+        let pairs: Vec<(usize, usize)> = index.pairs_within(1).into_iter().map(|(i, j, _)| (i, j)).collect();
+        assert_eq!(crate::day02::one_off_pairs(&ids), pairs);
+
+        // Brute force agrees for a wider radius too
+        let expected: Vec<(usize, usize, usize)> = (0 .. ids.len())
+            .flat_map(|i| (i + 1 .. ids.len()).map(move |j| (i, j)))
+            .filter_map(|(i, j)| distance(ids[i], ids[j]).map(|d| (i, j, d)))
+            .filter(|&(_, _, d)| d <= 20)
+            .collect();
+        assert_eq!(expected, index.pairs_within(20));
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use chrono::{NaiveDateTime, Timelike};
+
+use crate::grid::Grid;
+use crate::guard::Shift;
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+// A guard asleep from `time.start` up to, but not including, `time.end`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Nap {
+    pub guard: usize,
+    pub time: Range<NaiveDateTime>,
+}
+
+impl Nap {
+    pub fn minutes(&self) -> i64 {
+        (self.time.end - self.time.start).num_minutes()
+    }
+}
+
+// The minutes of each day that a sleep matrix counts
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Window {
+    // 00:00 to 00:59, as in the puzzle
+    MidnightHour,
+    // 00:00 to 23:59
+    FullDay,
+    // `minutes` long, starting `start` minutes after midnight; negative starts the evening before
+    Custom { start: i64, minutes: usize },
+}
+
+impl Window {
+    pub fn start(&self) -> i64 {
+        match self {
+            Window::MidnightHour | Window::FullDay => 0,
+            Window::Custom{start, ..} => *start,
+        }
+    }
+
+    // At most one day
+    pub fn len(&self) -> usize {
+        match self {
+            Window::MidnightHour => 60,
+            Window::FullDay => MINUTES_PER_DAY as usize,
+            Window::Custom{minutes, ..} => (*minutes).min(MINUTES_PER_DAY as usize),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Minutes from midnight for a column; negative before midnight
+    pub fn minute(&self, column: usize) -> i64 {
+        self.start() + column as i64
+    }
+
+    // Minutes after the window starts, wrapping around the day
+    fn offset(&self, ts: NaiveDateTime) -> usize {
+        let minute_of_day = (ts.hour() * 60 + ts.minute()) as i64;
+        (minute_of_day - self.start()).rem_euclid(MINUTES_PER_DAY) as usize
+    }
+
+}
+
+// Totals over every nap a guard took, in full
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NapStats {
+    pub count: usize,
+    pub minutes: i64,
+    pub longest: i64,
+}
+
+// One guard's row while a matrix is being built
+#[derive(Clone, Debug)]
+struct GuardRow {
+    guard: usize,
+    counts: Vec<usize>,
+    shifts: usize,
+    naps: NapStats,
+}
+
+// Accumulates shifts and naps in whatever order they come; rows are only sorted
+// into a grid by `build`
+#[derive(Clone, Debug)]
+pub struct SleepMatrixBuilder {
+    window: Window,
+    rows: HashMap<usize, usize>,
+    guards: Vec<GuardRow>,
+}
+
+impl SleepMatrixBuilder {
+    pub fn new(window: Window) -> SleepMatrixBuilder {
+        SleepMatrixBuilder{window, rows: HashMap::new(), guards: Vec::new()}
+    }
+
+    fn row(&mut self, guard: usize) -> &mut GuardRow {
+        let next = self.guards.len();
+        let idx = *self.rows.entry(guard).or_insert(next);
+        if idx == next {
+            self.guards.push(GuardRow{
+                guard,
+                counts: vec![0; self.window.len()],
+                shifts: 0,
+                naps: NapStats::default(),
+            });
+        }
+        &mut self.guards[idx]
+    }
+
+    pub fn add_shift(&mut self, shift: &Shift) {
+        self.add_guard_shift(shift.guard);
+        for nap in shift.absolute_naps() {
+            self.add_nap(&nap);
+        }
+    }
+
+    // A shift known only by its guard
+    pub fn add_guard_shift(&mut self, guard: usize) {
+        self.row(guard).shifts += 1;
+    }
+
+    pub fn add_nap(&mut self, nap: &Nap) {
+        let window = self.window;
+        let row = self.row(nap.guard);
+        row.naps.count += 1;
+        row.naps.minutes += nap.minutes();
+        row.naps.longest = row.naps.longest.max(nap.minutes());
+
+        // Every whole day covers each column once; the minutes left over run on from
+        // the start's offset, wrapping past the end of the day at most once
+        let minutes = nap.minutes().max(0);
+        let whole_days = (minutes / MINUTES_PER_DAY) as usize;
+        let leftover = (minutes % MINUTES_PER_DAY) as usize;
+        if whole_days > 0 {
+            for count in row.counts.iter_mut() {
+                *count += whole_days;
+            }
+        }
+        let start = window.offset(nap.time.start);
+        let end = start + leftover;
+        let day = MINUTES_PER_DAY as usize;
+        for range in [start .. end.min(day), 0 .. end.saturating_sub(day)] {
+            let range = range.start.min(row.counts.len()) .. range.end.min(row.counts.len());
+            for count in &mut row.counts[range] {
+                *count += 1;
+            }
+        }
+    }
+
+    pub fn build(mut self) -> SleepMatrix {
+        self.guards.sort_unstable_by_key(|row| row.guard);
+        let width = self.window.len();
+        let height = self.guards.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for row in &self.guards {
+            cells.extend_from_slice(&row.counts);
+        }
+        SleepMatrix{
+            guards: self.guards.iter().map(|row| row.guard).collect(),
+            window: self.window,
+            counts: Grid::from_vec(width, height, cells).unwrap(),
+            shifts: self.guards.iter().map(|row| row.shifts).collect(),
+            naps: self.guards.iter().map(|row| row.naps).collect(),
+        }
+    }
+}
+
+// How often each guard was asleep at each minute of a window, one row per guard
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SleepMatrix {
+    guards: Vec<usize>,
+    window: Window,
+    counts: Grid<usize>,
+    // Shifts worked by each guard, by row
+    shifts: Vec<usize>,
+    naps: Vec<NapStats>,
+}
+
+impl SleepMatrix {
+    // Each appearance of a guard in `shifts` is one shift; every guard gets a row, even if they never sleep
+    pub fn new<G>(shifts: G, naps: &[Nap], window: Window) -> SleepMatrix
+    where
+        G: IntoIterator<Item = usize>,
+    {
+        let mut builder = SleepMatrixBuilder::new(window);
+        for guard in shifts {
+            builder.add_guard_shift(guard);
+        }
+        for nap in naps {
+            builder.add_nap(nap);
+        }
+        builder.build()
+    }
+
+    pub fn from_shifts(shifts: &[Shift], window: Window) -> SleepMatrix {
+        let mut builder = SleepMatrixBuilder::new(window);
+        for shift in shifts {
+            builder.add_shift(shift);
+        }
+        builder.build()
+    }
+
+    // Ascending; row `i` of the matrix belongs to `guards()[i]`
+    pub fn guards(&self) -> &[usize] {
+        &self.guards
+    }
+
+    pub fn window(&self) -> Window {
+        self.window
+    }
+
+    pub fn counts(&self) -> &Grid<usize> {
+        &self.counts
+    }
+
+    pub fn row(&self, guard: usize) -> Option<&[usize]> {
+        let row = self.guards.binary_search(&guard).ok()?;
+        self.counts.row(row)
+    }
+
+    // Each guard with their row of counts
+    pub fn rows(&self) -> impl Iterator<Item = (usize, &[usize])> {
+        self.guards.iter().copied().zip(self.counts.rows())
+    }
+
+    pub fn shifts(&self, guard: usize) -> usize {
+        match self.guards.binary_search(&guard) {
+            Ok(row) => self.shifts[row],
+            Err(_) => 0,
+        }
+    }
+
+    pub fn nap_stats(&self, guard: usize) -> NapStats {
+        match self.guards.binary_search(&guard) {
+            Ok(row) => self.naps[row],
+            Err(_) => NapStats::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::guard::GuardLogs;
+    use crate::sleep::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(1518, 11, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_window_columns() {
+        let evening = Window::Custom{start: -60, minutes: 120};
+        assert_eq!(0, evening.offset(at(1, 23, 0)));
+        assert_eq!(119, evening.offset(at(2, 0, 59)));
+        assert_eq!(120, evening.offset(at(2, 1, 0)));
+        assert_eq!(-60, evening.minute(0));
+        assert_eq!(59, Window::MidnightHour.offset(at(1, 0, 59)));
+        assert_eq!(1380, Window::MidnightHour.offset(at(1, 23, 0)));
+    }
+
+    #[test]
+    fn test_nap_past_one_oclock() {
+        let naps = vec![Nap{guard: 10, time: at(1, 0, 50) .. at(1, 1, 10)}];
+
+        let hour = SleepMatrix::new(vec![], &naps, Window::MidnightHour);
+        assert_eq!(10, hour.row(10).unwrap().iter().sum::<usize>());
+
+        let day = SleepMatrix::new(vec![], &naps, Window::FullDay);
+        let row = day.row(10).unwrap();
+        assert_eq!(20, row.iter().sum::<usize>());
+        assert_eq!(1, row[69]);
+        assert_eq!(0, row[70]);
+    }
+
+    #[test]
+    fn test_nap_across_days() {
+        let naps = vec![
+            Nap{guard: 10, time: at(1, 0, 30) .. at(3, 0, 40)},
+            Nap{guard: 99, time: at(1, 0, 5) .. at(1, 0, 6)},
+        ];
+        let matrix = SleepMatrix::new(vec![7], &naps, Window::MidnightHour);
+
+        assert_eq!(&[7, 10, 99][..], matrix.guards());
+        assert_eq!(60, matrix.counts().width());
+        let row = matrix.row(10).unwrap();
+        assert_eq!(2, row[29]);
+        assert_eq!(3, row[30]);
+        assert_eq!(2, row[40]);
+        assert_eq!(2*24*60 + 10, naps[0].minutes());
+        assert_eq!(0, matrix.row(7).unwrap().iter().sum::<usize>());
+        assert_eq!(1, matrix.shifts(7));
+        assert_eq!(0, matrix.shifts(10));
+        assert_eq!(NapStats{count: 1, minutes: 2*24*60 + 10, longest: 2*24*60 + 10}, matrix.nap_stats(10));
+    }
+
+    #[test]
+    fn test_long_naps() {
+        let naps = vec![
+            // Three years and a bit, starting in the evening and wrapping past midnight
+            Nap{guard: 10, time: at(1, 23, 50) .. NaiveDate::from_ymd_opt(1521, 11, 2).unwrap().and_hms_opt(0, 20, 0).unwrap()},
+            // Exactly two days
+            Nap{guard: 99, time: at(1, 0, 30) .. at(3, 0, 30)},
+        ];
+        let days = (NaiveDate::from_ymd_opt(1521, 11, 1).unwrap() - NaiveDate::from_ymd_opt(1518, 11, 1).unwrap()).num_days() as usize;
+
+        let hour = SleepMatrix::new(vec![], &naps, Window::MidnightHour);
+        let row = hour.row(10).unwrap();
+        assert_eq!(days + 1, row[0]);
+        assert_eq!(days + 1, row[19]);
+        assert_eq!(days, row[20]);
+        assert!(hour.row(99).unwrap().iter().all(|&count| count == 2));
+
+        let evening = SleepMatrix::new(vec![], &naps, Window::Custom{start: -30, minutes: 60});
+        let row = evening.row(10).unwrap();
+        assert_eq!(days, row[19]);
+        assert_eq!(days + 1, row[20]);
+        assert_eq!(days + 1, row[49]);
+        assert_eq!(days, row[50]);
+        assert_eq!(naps[0].minutes() as usize, SleepMatrix::new(vec![], &naps[..1], Window::FullDay).row(10).unwrap().iter().sum::<usize>());
+    }
+
+    #[test]
+    fn test_from_shifts() {
+        let input = "\
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-01 23:59] falls asleep
+[1518-11-02 01:30] wakes up
+";
+        let shifts = GuardLogs::parse(input).unwrap().shifts().unwrap();
+        let matrix = SleepMatrix::from_shifts(&shifts, Window::Custom{start: -60, minutes: 180});
+        let row = matrix.row(99).unwrap();
+        assert_eq!(91, row.iter().sum::<usize>());
+        assert_eq!(0, row[58]);
+        assert_eq!(1, row[59]);
+        assert_eq!(0, row[150]);
+    }
+
+    #[test]
+    fn test_empty_window_keeps_guards() {
+        let naps = vec![Nap{guard: 99, time: at(1, 0, 5) .. at(3, 0, 6)}];
+        let matrix = SleepMatrix::new(vec![10, 99], &naps, Window::Custom{start: 0, minutes: 0});
+        assert_eq!(&[10, 99][..], matrix.guards());
+        let rows: Vec<(usize, &[usize])> = matrix.rows().collect();
+        assert_eq!(vec![(10, &[][..]), (99, &[][..])], rows);
+        assert_eq!(1, matrix.nap_stats(99).count);
+
+        let report = crate::report::SleepReport::new(&matrix);
+        assert_eq!(2, report.guards().len());
+        assert_eq!(None, report.guard(99).unwrap().busiest_minute);
+        for strategy in crate::strategy::builtin() {
+            assert!(strategy.choose(&matrix).is_some(), "{}", strategy.name());
+        }
+    }
+
+    #[test]
+    fn test_builder_sorts_rows() {
+        let mut builder = SleepMatrixBuilder::new(Window::MidnightHour);
+        for guard in (0 .. 1000).rev() {
+            builder.add_guard_shift(guard * 7 % 1000);
+        }
+        builder.add_nap(&Nap{guard: 3, time: at(1, 0, 10) .. at(1, 0, 12)});
+        let matrix = builder.build();
+
+        assert_eq!((0 .. 1000).collect::<Vec<usize>>(), matrix.guards());
+        assert_eq!(1000, matrix.counts().height());
+        assert_eq!(Some(&[1, 1][..]), matrix.row(3).map(|row| &row[10 .. 12]));
+        assert_eq!(2, matrix.counts().iter().sum::<usize>());
+    }
+}
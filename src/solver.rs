@@ -0,0 +1,178 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{day01, day02, day03, day04, day05};
+use crate::error::Error;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    A,
+    B,
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::A => write!(f, "a"),
+            Part::B => write!(f, "b"),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Part, String> {
+        match s {
+            "a" | "A" | "1" => Ok(Part::A),
+            "b" | "B" | "2" => Ok(Part::B),
+            _ => Err(format!("unknown part {:?}, expected a or b", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Integer(isize),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Integer(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(n: isize) -> Answer {
+        Answer::Integer(n)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Answer {
+        Answer::Integer(n as isize)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Answer {
+        Answer::Text(s)
+    }
+}
+
+impl<'a> From<&'a str> for Answer {
+    fn from(s: &'a str) -> Answer {
+        Answer::Text(s.to_string())
+    }
+}
+
+pub trait Solver {
+    fn day(&self) -> u8;
+    fn part(&self) -> Part;
+    fn solve(&self, input: &str) -> Result<Answer, Error>;
+}
+
+#[derive(Default)]
+pub struct Registry {
+    solvers: Vec<Box<dyn Solver>>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry{solvers: Vec::new()}
+    }
+
+    pub fn builtin() -> Registry {
+        let mut registry = Registry::new();
+        registry.register(Box::new(day01::Day01a));
+        registry.register(Box::new(day01::Day01b));
+        registry.register(Box::new(day02::Day02a));
+        registry.register(Box::new(day02::Day02b));
+        registry.register(Box::new(day03::Day03a));
+        registry.register(Box::new(day03::Day03b));
+        registry.register(Box::new(day04::Day04a));
+        registry.register(Box::new(day04::Day04b));
+        registry.register(Box::new(day05::Day05a));
+        registry.register(Box::new(day05::Day05b));
+        registry
+    }
+
+    // Replaces any solver already registered for the same day and part
+    pub fn register(&mut self, solver: Box<dyn Solver>) {
+        let key = (solver.day(), solver.part());
+        match self.solvers.binary_search_by_key(&key, |s| (s.day(), s.part())) {
+            Ok(idx) => self.solvers[idx] = solver,
+            Err(idx) => self.solvers.insert(idx, solver),
+        }
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<&dyn Solver> {
+        self.solvers.iter()
+            .find(|s| s.day() == day && s.part() == part)
+            .map(|s| s.as_ref())
+    }
+
+    // Sorted by day, then part
+    pub fn solvers(&self) -> &[Box<dyn Solver>] {
+        &self.solvers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solver::*;
+
+    struct Constant;
+
+    impl Solver for Constant {
+        fn day(&self) -> u8 { 1 }
+        fn part(&self) -> Part { Part::A }
+        fn solve(&self, _input: &str) -> Result<Answer, Error> { Ok(Answer::from(42usize)) }
+    }
+
+    #[test]
+    fn test_builtin_registry_is_sorted() {
+        let registry = Registry::builtin();
+        let keys: Vec<(u8, Part)> = registry.solvers().iter()
+            .map(|s| (s.day(), s.part()))
+            .collect();
+
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(sorted, keys);
+        assert_eq!(10, keys.len());
+    }
+
+    #[test]
+    fn test_registry_get() {
+        let registry = Registry::builtin();
+        assert!(registry.get(3, Part::B).is_some());
+        assert!(registry.get(25, Part::A).is_none());
+    }
+
+    #[test]
+    fn test_register_replaces_existing() {
+        let mut registry = Registry::builtin();
+        registry.register(Box::new(Constant));
+
+        assert_eq!(10, registry.solvers().len());
+        assert_eq!(Ok(Answer::Integer(42)), registry.get(1, Part::A).unwrap().solve(""));
+    }
+
+    #[test]
+    fn test_part_from_str() {
+        assert_eq!(Ok(Part::A), "a".parse());
+        assert_eq!(Ok(Part::B), "2".parse());
+        assert!("c".parse::<Part>().is_err());
+    }
+
+    #[test]
+    fn test_answer_display() {
+        assert_eq!("-7", Answer::from(-7isize).to_string());
+        assert_eq!("abc", Answer::from("abc").to_string());
+    }
+}
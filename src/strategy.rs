@@ -0,0 +1,208 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::sleep::SleepMatrix;
+
+// The guard and minute a strategy picked, with the figures behind the pick
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Choice {
+    pub guard: usize,
+    // Minutes from midnight; negative before midnight
+    pub minute: i64,
+    // Minutes asleep within the window
+    pub minutes_asleep: usize,
+    // Times asleep at `minute`
+    pub frequency: usize,
+    pub shifts: usize,
+    // What the strategy ranked guards by
+    pub score: i64,
+}
+
+impl Choice {
+    // Guard ID times minute, as the puzzle asks; None if that doesn't fit an isize
+    pub fn answer(&self) -> Option<isize> {
+        let guard = isize::try_from(self.guard).ok()?;
+        let minute = isize::try_from(self.minute).ok()?;
+        guard.checked_mul(minute)
+    }
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{} at minute {} (score {}, asleep {} minutes over {} shifts, {} times at that minute)",
+            self.guard, self.minute, self.score, self.minutes_asleep, self.shifts, self.frequency)
+    }
+}
+
+pub trait SleepStrategy {
+    fn name(&self) -> &'static str;
+
+    // None when the matrix has no guards
+    fn choose(&self, matrix: &SleepMatrix) -> Option<Choice>;
+}
+
+// The column a guard was most often asleep at and how often; ties go to the later column
+pub fn busiest_minute(row: &[usize]) -> (usize, usize) {
+    row.iter()
+        .copied()
+        .enumerate()
+        .max_by_key(|&(_, count)| count)
+        .unwrap_or((0, 0))
+}
+
+// Scores each guard's row as (column, score) and picks the highest score; ties go to the later guard
+fn best_by<F>(matrix: &SleepMatrix, score: F) -> Option<Choice>
+where
+    F: Fn(usize, &[usize]) -> (usize, i64),
+{
+    matrix.rows()
+        .map(|(guard, row)| {
+            let (column, score) = score(guard, row);
+            Choice{
+                guard,
+                minute: matrix.window().minute(column),
+                minutes_asleep: row.iter().sum(),
+                frequency: row.get(column).copied().unwrap_or(0),
+                shifts: matrix.shifts(guard),
+                score,
+            }
+        })
+        .max_by_key(|choice| choice.score)
+}
+
+// Puzzle strategy 1: the guard asleep longest, at their busiest minute
+pub struct MostMinutesAsleep;
+
+impl SleepStrategy for MostMinutesAsleep {
+    fn name(&self) -> &'static str { "most-minutes" }
+
+    fn choose(&self, matrix: &SleepMatrix) -> Option<Choice> {
+        best_by(matrix, |_, row| (busiest_minute(row).0, row.iter().sum::<usize>() as i64))
+    }
+}
+
+// Puzzle strategy 2: the guard most often asleep on the same minute
+pub struct MostFrequentMinute;
+
+impl SleepStrategy for MostFrequentMinute {
+    fn name(&self) -> &'static str { "most-frequent-minute" }
+
+    fn choose(&self, matrix: &SleepMatrix) -> Option<Choice> {
+        best_by(matrix, |_, row| {
+            let (column, count) = busiest_minute(row);
+            (column, count as i64)
+        })
+    }
+}
+
+// The guard asleep at their busiest minute on the largest percentage of their shifts
+pub struct MostConsistent;
+
+impl SleepStrategy for MostConsistent {
+    fn name(&self) -> &'static str { "most-consistent" }
+
+    fn choose(&self, matrix: &SleepMatrix) -> Option<Choice> {
+        best_by(matrix, |guard, row| {
+            let (column, count) = busiest_minute(row);
+            match matrix.shifts(guard) {
+                0 => (column, 0),
+                shifts => (column, (count * 100 / shifts) as i64),
+            }
+        })
+    }
+}
+
+// The guard asleep latest in the window, at that last minute
+pub struct LatestSleeper;
+
+impl SleepStrategy for LatestSleeper {
+    fn name(&self) -> &'static str { "latest" }
+
+    fn choose(&self, matrix: &SleepMatrix) -> Option<Choice> {
+        best_by(matrix, |_, row| {
+            match row.iter().rposition(|&count| count > 0) {
+                Some(column) => (column, matrix.window().minute(column)),
+                None => (0, i64::MIN),
+            }
+        })
+    }
+}
+
+// The guard with the longest single nap, window or not, at their busiest minute
+pub struct LongestNap;
+
+impl SleepStrategy for LongestNap {
+    fn name(&self) -> &'static str { "longest-nap" }
+
+    fn choose(&self, matrix: &SleepMatrix) -> Option<Choice> {
+        best_by(matrix, |guard, row| (busiest_minute(row).0, matrix.nap_stats(guard).longest))
+    }
+}
+
+pub fn builtin() -> Vec<Box<dyn SleepStrategy>> {
+    vec![
+        Box::new(MostMinutesAsleep),
+        Box::new(MostFrequentMinute),
+        Box::new(MostConsistent),
+        Box::new(LatestSleeper),
+        Box::new(LongestNap),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::guard::GuardLogs;
+    use crate::guard::tests::EXAMPLE;
+    use crate::sleep::Window;
+    use crate::strategy::*;
+
+    fn example() -> SleepMatrix {
+        let shifts = GuardLogs::parse(EXAMPLE).unwrap().shifts().unwrap();
+        SleepMatrix::from_shifts(&shifts, Window::MidnightHour)
+    }
+
+    #[test]
+    fn test_puzzle_strategies() {
+        let matrix = example();
+
+        let one = MostMinutesAsleep.choose(&matrix).unwrap();
+        assert_eq!((10, 24, 240), (one.guard, one.minute, one.answer().unwrap()));
+        assert_eq!((50, 2, 2), (one.minutes_asleep, one.frequency, one.shifts));
+
+        let two = MostFrequentMinute.choose(&matrix).unwrap();
+        assert_eq!((99, 45, 4455), (two.guard, two.minute, two.answer().unwrap()));
+        assert_eq!(3, two.frequency);
+    }
+
+    #[test]
+    fn test_custom_strategies() {
+        let matrix = example();
+
+        let consistent = MostConsistent.choose(&matrix).unwrap();
+        assert_eq!((99, 100), (consistent.guard, consistent.score));
+
+        let latest = LatestSleeper.choose(&matrix).unwrap();
+        assert_eq!((99, 54), (latest.guard, latest.minute));
+
+        let longest = LongestNap.choose(&matrix).unwrap();
+        assert_eq!((10, 25), (longest.guard, longest.score));
+    }
+
+    #[test]
+    fn test_answer_overflow() {
+        let choice = |guard, minute| Choice{guard, minute, minutes_asleep: 0, frequency: 0, shifts: 1, score: 0};
+        assert_eq!(Some(-120), choice(2, -60).answer());
+        assert_eq!(Some(9_000_000_000_000_000_000), choice(9_000_000_000_000_000_000, 1).answer());
+        assert_eq!(None, choice(9_000_000_000_000_000_000, 2).answer());
+        assert_eq!(None, choice(usize::MAX, 1).answer());
+        assert_eq!(None, choice(isize::MAX as usize, 2).answer());
+    }
+
+    #[test]
+    fn test_no_guards() {
+        let matrix = SleepMatrix::new(vec![], &[], Window::MidnightHour);
+        for strategy in builtin() {
+            assert_eq!(None, strategy.choose(&matrix), "{}", strategy.name());
+        }
+    }
+}
@@ -0,0 +1,443 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::env;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::ParseError;
+use crate::guard::{Diagnostic, LogEntry, ShiftTracker};
+use crate::parse::GuardLog;
+use crate::sleep::{SleepMatrix, SleepMatrixBuilder, Window};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InputOrder {
+    // Chronological already, e.g. after `sort`; streamed straight into the matrix
+    Sorted,
+    // Sorted in chunks spilled to temporary files, then merged
+    Unsorted,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamOptions {
+    order: InputOrder,
+    chunk_lines: usize,
+    window: Window,
+}
+
+impl Default for StreamOptions {
+    fn default() -> StreamOptions {
+        StreamOptions{order: InputOrder::Unsorted, chunk_lines: 100_000, window: Window::MidnightHour}
+    }
+}
+
+impl StreamOptions {
+    pub fn new() -> StreamOptions {
+        StreamOptions::default()
+    }
+
+    pub fn order(mut self, order: InputOrder) -> StreamOptions {
+        self.order = order;
+        self
+    }
+
+    // Entries held in memory at once when sorting, and how often progress is reported
+    pub fn chunk_lines(mut self, lines: usize) -> StreamOptions {
+        self.chunk_lines = lines.max(1);
+        self
+    }
+
+    pub fn window(mut self, window: Window) -> StreamOptions {
+        self.window = window;
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Progress {
+    // Log entries read so far
+    Read { lines: usize },
+    // A sorted chunk was written to a temporary file
+    Spilled { chunk: usize, lines: usize },
+    // Log entries fed into the sleep matrix so far
+    Applied { entries: usize },
+}
+
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    Parse(ParseError),
+    // A log declared sorted goes back in time
+    OutOfOrder { line: usize, previous_line: usize },
+    InvalidLog(Diagnostic),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "{}", e),
+            StreamError::Parse(e) => write!(f, "{}", e),
+            StreamError::OutOfOrder{line, previous_line} => {
+                write!(f, "line {}: earlier than line {} in a log read as sorted", line, previous_line)
+            },
+            StreamError::InvalidLog(d) => write!(f, "{}", d),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> StreamError {
+        StreamError::Io(e)
+    }
+}
+
+impl From<ParseError> for StreamError {
+    fn from(e: ParseError) -> StreamError {
+        StreamError::Parse(e)
+    }
+}
+
+impl From<Diagnostic> for StreamError {
+    fn from(d: Diagnostic) -> StreamError {
+        StreamError::InvalidLog(d)
+    }
+}
+
+// Non-blank lines as numbered entries
+fn entries<R: BufRead>(input: R) -> impl Iterator<Item = Result<LogEntry, StreamError>> {
+    input.lines()
+        .enumerate()
+        .filter_map(|(idx, line)| match line {
+            Err(e) => Some(Err(e.into())),
+            Ok(ref line) if line.trim().is_empty() => None,
+            Ok(line) => Some(line.parse::<GuardLog>()
+                .map(|log| LogEntry{line: idx + 1, log})
+                .map_err(|e| e.at_line(idx + 1).into())),
+        })
+}
+
+// Feeds chronological entries into the matrix a shift at a time
+struct Sink<'a> {
+    tracker: ShiftTracker,
+    matrix: SleepMatrixBuilder,
+    entries: usize,
+    report_every: usize,
+    progress: &'a mut dyn FnMut(Progress),
+}
+
+impl<'a> Sink<'a> {
+    fn push(&mut self, entry: &LogEntry) -> Result<(), Diagnostic> {
+        if let Some(shift) = self.tracker.push(entry)? {
+            self.matrix.add_shift(&shift);
+        }
+        self.entries += 1;
+        if self.entries.is_multiple_of(self.report_every) {
+            (self.progress)(Progress::Applied{entries: self.entries});
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<SleepMatrix, Diagnostic> {
+        if let Some(shift) = self.tracker.finish()? {
+            self.matrix.add_shift(&shift);
+        }
+        if !self.entries.is_multiple_of(self.report_every) {
+            (self.progress)(Progress::Applied{entries: self.entries});
+        }
+        Ok(self.matrix.build())
+    }
+}
+
+// Same-minute entries may come in any order, so each minute is buffered in read order
+// and sorted once before it is applied
+fn stream_sorted<R: BufRead>(input: R, sink: &mut Sink) -> Result<(), StreamError> {
+    let mut minute: Vec<LogEntry> = Vec::new();
+    let mut read = 0usize;
+    for entry in entries(input) {
+        let entry = entry?;
+        // Every buffered entry has the same timestamp, and the last one was read last
+        if let Some(previous) = minute.last() {
+            if entry.log.ts < previous.log.ts {
+                return Err(StreamError::OutOfOrder{line: entry.line, previous_line: previous.line});
+            }
+            if entry.log.ts > previous.log.ts {
+                sort_chunk(&mut minute);
+                for entry in minute.drain(..) {
+                    sink.push(&entry)?;
+                }
+            }
+        }
+        minute.push(entry);
+
+        read += 1;
+        if read.is_multiple_of(sink.report_every) {
+            (sink.progress)(Progress::Read{lines: read});
+        }
+    }
+    sort_chunk(&mut minute);
+    for entry in &minute {
+        sink.push(entry)?;
+    }
+    Ok(())
+}
+
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// A sorted chunk in a temporary file, removed when dropped
+struct Run {
+    path: PathBuf,
+}
+
+impl Run {
+    fn write(entries: &[LogEntry]) -> io::Result<Run> {
+        Run::write_in(&env::temp_dir(), entries)
+    }
+
+    // The directory may be shared, so only a path that did not exist yet is used; a
+    // file or symlink planted under the same name is skipped rather than overwritten
+    fn write_in(dir: &Path, entries: &[LogEntry]) -> io::Result<Run> {
+        let (run, file) = loop {
+            let id = RUN_COUNTER.fetch_add(1, Ordering::Relaxed);
+            let path = dir.join(format!("advent2018-{}-{}.log", process::id(), id));
+            let mut options = OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+            match options.open(&path) {
+                Ok(file) => break (Run{path}, file),
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        };
+
+        let mut file = BufWriter::new(file);
+        for entry in entries {
+            writeln!(file, "{}\t{}", entry.line, entry.log)?;
+        }
+        file.flush()?;
+        Ok(run)
+    }
+
+    fn entries(&self) -> io::Result<impl Iterator<Item = io::Result<LogEntry>>> {
+        let file = BufReader::new(File::open(&self.path)?);
+        Ok(file.lines().map(|line| {
+            let line = line?;
+            let corrupt = || io::Error::new(io::ErrorKind::InvalidData, format!("corrupt sort chunk: {:?}", line));
+            let (number, log) = line.split_once('\t').ok_or_else(corrupt)?;
+            Ok(LogEntry{
+                line: number.parse().map_err(|_| corrupt())?,
+                log: log.parse().map_err(|_| corrupt())?,
+            })
+        }))
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn sort_chunk(chunk: &mut [LogEntry]) {
+    chunk.sort_by(|a, b| a.log.cmp(&b.log).then(a.line.cmp(&b.line)));
+}
+
+// External merge sort; input that fits in one chunk never touches the disk
+fn stream_unsorted<R: BufRead>(input: R, chunk_lines: usize, sink: &mut Sink) -> Result<(), StreamError> {
+    let mut chunk: Vec<LogEntry> = Vec::new();
+    let mut runs: Vec<Run> = Vec::new();
+    let mut read = 0usize;
+    for entry in entries(input) {
+        chunk.push(entry?);
+        read += 1;
+        if read.is_multiple_of(chunk_lines) {
+            (sink.progress)(Progress::Read{lines: read});
+            sort_chunk(&mut chunk);
+            runs.push(Run::write(&chunk)?);
+            (sink.progress)(Progress::Spilled{chunk: runs.len(), lines: chunk.len()});
+            chunk.clear();
+        }
+    }
+    sort_chunk(&mut chunk);
+
+    if runs.is_empty() {
+        for entry in &chunk {
+            sink.push(entry)?;
+        }
+        return Ok(());
+    }
+    if !chunk.is_empty() {
+        runs.push(Run::write(&chunk)?);
+        (sink.progress)(Progress::Spilled{chunk: runs.len(), lines: chunk.len()});
+    }
+    drop(chunk);
+
+    let mut readers = runs.iter()
+        .map(Run::entries)
+        .collect::<io::Result<Vec<_>>>()?;
+    let mut heap = BinaryHeap::new();
+    for (idx, reader) in readers.iter_mut().enumerate() {
+        if let Some(entry) = reader.next() {
+            let entry = entry?;
+            heap.push(Reverse((entry.log, entry.line, idx)));
+        }
+    }
+    while let Some(Reverse((log, line, idx))) = heap.pop() {
+        sink.push(&LogEntry{line, log})?;
+        if let Some(entry) = readers[idx].next() {
+            let entry = entry?;
+            heap.push(Reverse((entry.log, entry.line, idx)));
+        }
+    }
+    Ok(())
+}
+
+// Builds a sleep matrix from a guard log without holding the whole log in memory
+pub fn ingest<R, P>(input: R, options: &StreamOptions, mut progress: P) -> Result<SleepMatrix, StreamError>
+where
+    R: BufRead,
+    P: FnMut(Progress),
+{
+    let mut sink = Sink{
+        tracker: ShiftTracker::new(),
+        matrix: SleepMatrixBuilder::new(options.window),
+        entries: 0,
+        report_every: options.chunk_lines,
+        progress: &mut progress,
+    };
+    match options.order {
+        InputOrder::Sorted => stream_sorted(input, &mut sink)?,
+        InputOrder::Unsorted => stream_unsorted(input, options.chunk_lines, &mut sink)?,
+    }
+    Ok(sink.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::guard::GuardLogs;
+    use crate::stream::*;
+
+    fn in_memory(input: &str) -> SleepMatrix {
+        let shifts = GuardLogs::parse(input).unwrap().shifts().unwrap();
+        SleepMatrix::from_shifts(&shifts, Window::MidnightHour)
+    }
+
+    #[test]
+    fn test_unsorted_spills_and_merges() {
+        let input = crate::input::EmbeddedInput::get(4).unwrap();
+        let lines = input.lines().filter(|line| !line.trim().is_empty()).count();
+        let mut events = Vec::new();
+        let options = StreamOptions::new().chunk_lines(100);
+
+        let matrix = ingest(Cursor::new(input), &options, |p| events.push(p)).unwrap();
+        assert_eq!(in_memory(input), matrix);
+
+        let spilled = events.iter().filter(|p| matches!(p, Progress::Spilled{..})).count();
+        assert_eq!(lines.div_ceil(100), spilled);
+        assert_eq!(Some(&Progress::Applied{entries: lines}), events.last());
+    }
+
+    #[test]
+    fn test_unsorted_single_chunk() {
+        let input = crate::input::EmbeddedInput::get(4).unwrap();
+        let mut events = Vec::new();
+        let matrix = ingest(Cursor::new(input), &StreamOptions::new(), |p| events.push(p)).unwrap();
+        assert_eq!(in_memory(input), matrix);
+        assert!(!events.iter().any(|p| matches!(p, Progress::Spilled{..})));
+    }
+
+    #[test]
+    fn test_sorted() {
+        let input = crate::input::EmbeddedInput::get(4).unwrap();
+        let mut lines: Vec<&str> = input.lines().collect();
+        lines.sort();
+        let sorted = lines.join("\n");
+
+        let options = StreamOptions::new().order(InputOrder::Sorted);
+        assert_eq!(in_memory(input), ingest(Cursor::new(sorted), &options, |_| ()).unwrap());
+    }
+
+    #[test]
+    fn test_sorted_same_minute_in_any_order() {
+        let input = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-02 00:00] Guard #99 begins shift
+[1518-11-02 00:00] wakes up
+";
+        let options = StreamOptions::new().order(InputOrder::Sorted);
+        assert_eq!(in_memory(input), ingest(Cursor::new(input), &options, |_| ()).unwrap());
+    }
+
+    #[test]
+    fn test_spill_skips_existing_paths() {
+        let dir = env::temp_dir().join(format!("advent2018-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let next = RUN_COUNTER.load(Ordering::Relaxed);
+        let planted: Vec<PathBuf> = (next .. next + 20)
+            .map(|id| dir.join(format!("advent2018-{}-{}.log", process::id(), id)))
+            .collect();
+        for path in &planted {
+            fs::write(path, "keep").unwrap();
+        }
+
+        let entries = GuardLogs::parse("[1518-11-01 00:00] Guard #10 begins shift\n").unwrap();
+        let entries: Vec<LogEntry> = entries.iter().cloned().collect();
+        let run = Run::write_in(&dir, &entries).unwrap();
+        assert!(!planted.contains(&run.path));
+        assert_eq!(entries, run.entries().unwrap().collect::<io::Result<Vec<_>>>().unwrap());
+        for path in &planted {
+            assert_eq!("keep", fs::read_to_string(path).unwrap());
+        }
+
+        drop(run);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_errors() {
+        let options = StreamOptions::new().order(InputOrder::Sorted);
+        let input = "\
+[1518-11-02 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+";
+        match ingest(Cursor::new(input), &options, |_| ()) {
+            Err(StreamError::OutOfOrder{line: 2, previous_line: 1}) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Blamed on the line read just before, however the same-minute entries sort
+        for order in &[["wakes up", "falls asleep"], ["falls asleep", "wakes up"]] {
+            let input = format!("\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] {}
+[1518-11-01 00:05] {}
+[1518-11-01 00:04] falls asleep
+", order[0], order[1]);
+            match ingest(Cursor::new(input), &options, |_| ()) {
+                Err(StreamError::OutOfOrder{line: 4, previous_line: 3}) => (),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+
+        match ingest(Cursor::new("\n[1518-11-01 00:05] wakes up\n"), &StreamOptions::new(), |_| ()) {
+            Err(StreamError::InvalidLog(Diagnostic::BeforeFirstShift{line: 2})) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        match ingest(Cursor::new("nonsense\n"), &StreamOptions::new(), |_| ()) {
+            Err(StreamError::Parse(e)) => assert_eq!(Some(1), e.line()),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt::Write;
+
+use chrono::NaiveDate;
+
+use crate::guard::Shift;
+use crate::sleep::Window;
+
+// Which shifts a timeline shows; everything by default
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimelineFilter {
+    guard: Option<usize>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+impl TimelineFilter {
+    pub fn new() -> TimelineFilter {
+        TimelineFilter::default()
+    }
+
+    pub fn guard(mut self, guard: usize) -> TimelineFilter {
+        self.guard = Some(guard);
+        self
+    }
+
+    // Nights on or after `date`
+    pub fn from(mut self, date: NaiveDate) -> TimelineFilter {
+        self.from = Some(date);
+        self
+    }
+
+    // Nights on or before `date`
+    pub fn to(mut self, date: NaiveDate) -> TimelineFilter {
+        self.to = Some(date);
+        self
+    }
+
+    pub fn matches(&self, shift: &Shift) -> bool {
+        self.guard.is_none_or(|guard| shift.guard == guard)
+            && self.from.is_none_or(|from| shift.night >= from)
+            && self.to.is_none_or(|to| shift.night <= to)
+    }
+}
+
+// The puzzle's chart: one row per shift, `#` asleep and `.` awake for each minute of the window
+pub fn render(shifts: &[Shift], window: Window, filter: &TimelineFilter) -> String {
+    let shown: Vec<&Shift> = shifts.iter()
+        .filter(|shift| filter.matches(shift))
+        .collect();
+    let id_width = shown.iter()
+        .map(|shift| format!("#{}", shift.guard).len())
+        .max()
+        .unwrap_or(0)
+        .max("ID".len());
+    let minutes: Vec<i64> = (0 .. window.len())
+        .map(|column| window.minute(column))
+        .collect();
+
+    let mut chart = String::new();
+    writeln!(chart, "{:<5}  {:<w$}  Minute", "Date", "ID", w = id_width).unwrap();
+    let tens: String = minutes.iter()
+        .map(|m| (b'0' + (m.rem_euclid(60) / 10) as u8) as char)
+        .collect();
+    let units: String = minutes.iter()
+        .map(|m| (b'0' + (m.rem_euclid(60) % 10) as u8) as char)
+        .collect();
+    writeln!(chart, "{:<5}  {:<w$}  {}", "", "", tens, w = id_width).unwrap();
+    writeln!(chart, "{:<5}  {:<w$}  {}", "", "", units, w = id_width).unwrap();
+
+    for shift in shown {
+        let row: String = minutes.iter()
+            .map(|&m| match shift.is_asleep(m) {
+                true => '#',
+                false => '.',
+            })
+            .collect();
+        writeln!(chart, "{}  {:<w$}  {}",
+            shift.night.format("%m-%d"), format!("#{}", shift.guard), row, w = id_width).unwrap();
+    }
+    chart
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::guard::GuardLogs;
+    use crate::guard::tests::EXAMPLE;
+    use crate::timeline::*;
+
+    // As printed in the puzzle
+    const CHART: &str = "\
+Date   ID   Minute
+            000000000011111111112222222222333333333344444444445555555555
+            012345678901234567890123456789012345678901234567890123456789
+11-01  #10  .....####################.....#########################.....
+11-02  #99  ........................................##########..........
+11-03  #10  ........................#####...............................
+11-04  #99  ....................................##########..............
+11-05  #99  .............................................##########.....
+";
+
+    fn shifts() -> Vec<Shift> {
+        GuardLogs::parse(EXAMPLE).unwrap().shifts().unwrap()
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(1518, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_puzzle_chart() {
+        assert_eq!(CHART, render(&shifts(), Window::MidnightHour, &TimelineFilter::new()));
+    }
+
+    #[test]
+    fn test_filters() {
+        let by_guard = render(&shifts(), Window::MidnightHour, &TimelineFilter::new().guard(10));
+        let rows: Vec<&str> = by_guard.lines().skip(3).map(|row| &row[.. 5]).collect();
+        assert_eq!(vec!["11-01", "11-03"], rows);
+
+        let filter = TimelineFilter::new().guard(99).from(date(3)).to(date(4));
+        let by_date = render(&shifts(), Window::MidnightHour, &filter);
+        let rows: Vec<&str> = by_date.lines().skip(3).map(|row| &row[.. 5]).collect();
+        assert_eq!(vec!["11-04"], rows);
+
+        let none = render(&shifts(), Window::MidnightHour, &TimelineFilter::new().guard(7));
+        assert_eq!(3, none.lines().count());
+    }
+
+    #[test]
+    fn test_evening_window() {
+        let chart = render(&shifts(), Window::Custom{start: -5, minutes: 10}, &TimelineFilter::new().to(date(2)));
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!("            5555500000", lines[1]);
+        assert_eq!("            5678901234", lines[2]);
+        assert_eq!("11-01  #10  ..........", lines[3]);
+    }
+}